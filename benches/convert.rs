@@ -0,0 +1,27 @@
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+
+use n64rom::convert;
+use n64rom::rom::Endianness;
+
+/// Size of the buffer to convert, matching a large 32 MiB rom.
+const SIZE: usize = 32 * 1024 * 1024;
+
+fn buffer() -> Vec<u8> {
+    (0..SIZE).map(|i| (i * 31 + (i >> 12)) as u8).collect()
+}
+
+#[bench]
+fn convert_big_to_little(b: &mut Bencher) {
+    let mut data = buffer();
+    b.iter(|| convert::convert(&mut data, Endianness::Big, Endianness::Little).unwrap());
+}
+
+#[bench]
+fn convert_big_to_mixed(b: &mut Bencher) {
+    let mut data = buffer();
+    b.iter(|| convert::convert(&mut data, Endianness::Big, Endianness::Mixed).unwrap());
+}
@@ -1,28 +1,49 @@
-use clap::{App, Arg, ArgMatches};
-use std::fs::{File, OpenOptions};
-use std::io::{self, Seek, SeekFrom, Write};
+use clap::{App, Arg, ArgGroup, ArgMatches};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+#[cfg(feature = "color")]
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 use thiserror::Error;
 
 use n64rom::convert::{self, ConvertStatus};
-use n64rom::rom::{Endianness, Rom};
+use n64rom::header::{Header, Magic, MagicKind};
+use n64rom::ipl3::{Cic, IPL3};
+use n64rom::rom::{self, Endianness, Rom, VerifyStatus, HEAD_SIZE};
 use n64rom::stream::Writer;
-use n64rom::util::{FileSize, MEBIBYTE};
+use n64rom::util::{hexdump_lines, FileSize};
 
 #[derive(Debug, Error)]
 enum Error {
+    /// One or more rom files failed during a batch operation.
+    #[error("{0} of {1} rom files failed")]
+    BatchError(usize, usize),
     #[error("{0}")]
     ConvertError(#[from] n64rom::convert::Error),
     /// Invalid CRC values.
-    #[error("Bad CRC values, expected: ({0:#08X}, {1:#08X})")]
+    #[error("Bad CRC values, expected: ({0:#010X}, {1:#010X})")]
     CRCError(u32, u32),
+    /// Compared rom files differ.
+    #[error("Rom files differ")]
+    DiffError,
     /// Error parsing Header.
     #[error("{0}")]
     HeaderError(#[from] n64rom::header::Error),
     /// IO error.
     #[error("{0}")]
     IOError(#[from] io::Error),
+    #[error("{0}")]
+    IPL3Error(#[from] n64rom::ipl3::Error),
+    #[error("{0}")]
+    RomError(#[from] n64rom::rom::Error),
+    /// Warning treated as an error.
+    #[error("{0}")]
+    WarningError(String),
 }
 
 fn main() -> Result<(), Error> {
@@ -30,28 +51,173 @@ fn main() -> Result<(), Error> {
         .author("saneki <s@neki.me>")
         .version("0.1.0")
         .about("Displays information about N64 ROM files")
+        .subcommand(
+            App::new("normalize")
+                .about("Clean up a rom file, reporting each action taken")
+                .arg(Arg::with_name("trim")
+                    .long("trim")
+                    .help("Trim trailing overdump data down to the likely true rom size"))
+                .arg(Arg::with_name("pad")
+                    .long("pad")
+                    .help("Pad the rom with zeros up to a power-of-two size"))
+                .arg(Arg::with_name("fix-magic")
+                    .long("fix-magic")
+                    .help("Rewrite a corrupt magic number with the canonical value, after confirmation"))
+                .arg(Arg::with_name("fix-crc")
+                    .long("fix-crc")
+                    .help("Correct the CRC values"))
+                .arg(Arg::with_name("fix-all")
+                    .long("fix-all")
+                    .help("Perform all of the above"))
+                .arg(Arg::with_name("as")
+                    .long("as")
+                    .takes_value(true)
+                    .value_name("order")
+                    .possible_values(&["big", "little", "mixed"])
+                    .default_value("big")
+                    .help("Byte order of the rom file if the magic number is corrupt"))
+                .arg(Arg::with_name("yes")
+                    .long("yes")
+                    .short("y")
+                    .help("Do not ask for confirmation"))
+                .arg(Arg::with_name("output")
+                    .long("output")
+                    .short("o")
+                    .takes_value(true)
+                    .value_name("file")
+                    .help("Output rom file, instead of writing to the input rom file"))
+                .arg(Arg::with_name("file")
+                    .required(true)
+                    .help("Rom file"))
+                .group(ArgGroup::with_name("actions")
+                    .args(&["trim", "pad", "fix-magic", "fix-crc", "fix-all"])
+                    .multiple(true)
+                    .required(true))
+        )
+        .subcommand(
+            App::new("repair")
+                .about("Repair corrupt fields of a rom file")
+                .arg(Arg::with_name("magic")
+                    .long("magic")
+                    .help("Rewrite the magic number with the canonical value"))
+                .arg(Arg::with_name("as")
+                    .long("as")
+                    .takes_value(true)
+                    .value_name("order")
+                    .possible_values(&["big", "little", "mixed"])
+                    .default_value("big")
+                    .help("Byte order of the rom file, used instead of inferring it from the magic number"))
+                .arg(Arg::with_name("file")
+                    .required(true)
+                    .help("Rom file"))
+                .group(ArgGroup::with_name("repairs")
+                    .args(&["magic"])
+                    .multiple(true)
+                    .required(true))
+        )
         .subcommand(
             App::new("show")
                 .about("Show details about a rom file")
+                .arg(Arg::with_name("color")
+                    .long("color")
+                    .takes_value(true)
+                    .value_name("when")
+                    .possible_values(&["auto", "always", "never"])
+                    .default_value("auto")
+                    .help("Highlight problem fields (requires the color feature)"))
+                .arg(Arg::with_name("strict")
+                    .long("strict")
+                    .help("Treat warnings as errors"))
+                .arg(Arg::with_name("verify")
+                    .long("verify")
+                    .help("Also verify the CRC values, which requires reading the entire rom file"))
+                .arg(Arg::with_name("json")
+                    .long("json")
+                    .conflicts_with_all(&["verify", "hashes"])
+                    .help("Show a JSON object instead, with the fields: name (trimmed string), cic (short \
+                           code or \"unknown\"), byte_order (\"big\", \"little\" or \"mixed\"), crc1, crc2 \
+                           and entry_point (header values as integers), media (4-character string) and \
+                           size_bytes (file size)"))
+                .arg(Arg::with_name("hashes")
+                    .long("hashes")
+                    .help("Also show MD5 and SHA1 digests, which requires reading the entire rom file (requires the hashes feature)"))
                 .arg(Arg::with_name("file")
                     .required(true)
                     .help("Rom file"))
         )
+        .subcommand(
+            App::new("build")
+                .about("Build a big-endian rom file from an IPL3 bootcode file and program data")
+                .arg(Arg::with_name("ipl3")
+                    .long("ipl3")
+                    .takes_value(true)
+                    .value_name("file")
+                    .required(true)
+                    .help("IPL3 bootcode file, without the rom header"))
+                .arg(Arg::with_name("program")
+                    .long("program")
+                    .takes_value(true)
+                    .value_name("file")
+                    .required(true)
+                    .help("Program data to place after the IPL3"))
+                .arg(Arg::with_name("name")
+                    .long("name")
+                    .takes_value(true)
+                    .default_value("")
+                    .validator(validate_name)
+                    .help("Internal rom name, up to 20 bytes"))
+                .arg(Arg::with_name("entry")
+                    .long("entry")
+                    .takes_value(true)
+                    .value_name("address")
+                    .default_value("0x80000400")
+                    .validator(validate_address)
+                    .help("Program entry point, which is offset for the IPL3 in the header"))
+                .arg(Arg::with_name("media")
+                    .long("media")
+                    .takes_value(true)
+                    .value_name("code")
+                    .validator(validate_media)
+                    .help("Media format as 4 characters, such as NTEE"))
+                .arg(Arg::with_name("out")
+                    .long("out")
+                    .takes_value(true)
+                    .value_name("rom")
+                    .required(true)
+                    .help("Output rom file"))
+        )
         .subcommand(
             App::new("check")
-                .about("Verify whether or not the CRC values of a rom file are correct")
+                .about("Verify whether or not the CRC values of rom files are correct")
+                .arg(Arg::with_name("threads")
+                    .long("threads")
+                    .takes_value(true)
+                    .value_name("N")
+                    .validator(validate_threads)
+                    .help("Number of rom files to check concurrently (defaults to available parallelism)"))
                 .arg(Arg::with_name("file")
                     .required(true)
-                    .help("Rom file"))
+                    .multiple(true)
+                    .help("Rom file(s)"))
         )
         .subcommand(
             App::new("convert")
                 .about("Convert a rom file to a different byte order")
                 .arg(Arg::with_name("order")
                     .takes_value(true)
-                    .possible_values(&["big", "little", "mixed"])
+                    .possible_values(&["auto", "big", "little", "mixed"])
                     .required(true)
-                    .help("Byte order to convert to"))
+                    .help("Byte order to convert to, or auto to convert any byte order to big-endian"))
+                .arg(Arg::with_name("from")
+                    .long("from")
+                    .takes_value(true)
+                    .value_name("order")
+                    .possible_values(&["big", "little", "mixed"])
+                    .conflicts_with("trim")
+                    .help("Byte order of the input rom file, used instead of inferring it from the magic number"))
+                .arg(Arg::with_name("trim")
+                    .long("trim")
+                    .help("Trim trailing overdump data down to the likely true rom size"))
                 .arg(Arg::with_name("input")
                     .required(true)
                     .help("Input rom file"))
@@ -59,9 +225,97 @@ fn main() -> Result<(), Error> {
                     .required(true)
                     .help("Output rom file"))
         )
+        .subcommand(
+            App::new("detect")
+                .about("Report everything which can be inferred about an unknown file")
+                .arg(Arg::with_name("file")
+                    .required(true)
+                    .help("File to inspect"))
+        )
+        .subcommand(
+            App::new("diff")
+                .about("Compare two rom files, failing if they differ")
+                .arg(Arg::with_name("a")
+                    .required(true)
+                    .help("First rom file"))
+                .arg(Arg::with_name("b")
+                    .required(true)
+                    .help("Second rom file"))
+        )
+        .subcommand(
+            App::new("dump")
+                .about("Show a hex dump of a region of a rom file, defaulting to the header")
+                .arg(Arg::with_name("offset")
+                    .long("offset")
+                    .takes_value(true)
+                    .value_name("N")
+                    .validator(validate_length)
+                    .help("Offset of the first byte to show"))
+                .arg(Arg::with_name("length")
+                    .long("length")
+                    .takes_value(true)
+                    .value_name("N")
+                    .validator(validate_length)
+                    .help("Amount of bytes to show"))
+                .arg(Arg::with_name("order")
+                    .long("order")
+                    .takes_value(true)
+                    .possible_values(&["big", "little", "mixed"])
+                    .default_value("big")
+                    .help("Byte order to show the bytes in"))
+                .arg(Arg::with_name("file")
+                    .required(true)
+                    .help("Rom file"))
+        )
+        .subcommand(
+            App::new("extract")
+                .about("Extract part of a rom file, defaulting to the big-endian body")
+                .arg(Arg::with_name("boot")
+                    .long("boot")
+                    .takes_value(true)
+                    .value_name("len")
+                    .validator(validate_length)
+                    .help("Extract the header, IPL3 and the first <len> bytes of the body"))
+                .arg(Arg::with_name("head")
+                    .long("head")
+                    .help("Extract only the header and IPL3, which identify the rom without its body"))
+                .arg(Arg::with_name("order")
+                    .long("order")
+                    .takes_value(true)
+                    .possible_values(&["big", "little", "mixed"])
+                    .requires("head")
+                    .help("Byte order to write the head in, defaults to the original byte order"))
+                .arg(Arg::with_name("with-ipl3")
+                    .long("with-ipl3")
+                    .conflicts_with("part")
+                    .help("Include the IPL3 bootcode before the body"))
+                .arg(Arg::with_name("input")
+                    .required(true)
+                    .help("Input rom file"))
+                .arg(Arg::with_name("output")
+                    .required(true)
+                    .help("Output file"))
+                .group(ArgGroup::with_name("part")
+                    .args(&["boot", "head"]))
+        )
+        .subcommand(
+            App::new("extract-ipl3")
+                .about("Extract the IPL3 bootcode of a rom file, for reuse with build")
+                .arg(Arg::with_name("input")
+                    .required(true)
+                    .help("Input rom file"))
+                .arg(Arg::with_name("output")
+                    .required(true)
+                    .help("Output bootcode file"))
+        )
         .subcommand(
             App::new("correct")
                 .about("Correct the CRC values of a rom file")
+                .arg(Arg::with_name("order")
+                    .long("order")
+                    .takes_value(true)
+                    .possible_values(&["big", "little", "mixed"])
+                    .help("Byte order to rewrite the rom in, defaults to the original byte order"))
                 .arg(Arg::with_name("file")
                     .required(true)
                     .help("Rom file"))
@@ -70,7 +324,7 @@ fn main() -> Result<(), Error> {
 
     match main_with_args(&matches) {
         Ok(()) => Ok(()),
-        Err(Error::HeaderError(err)) => {
+        Err(Error::HeaderError(err)) | Err(Error::RomError(rom::Error::HeaderError(err))) => {
             println!("Error: {}, are you sure this is a rom file?", err);
             process::exit(1);
         }
@@ -100,45 +354,432 @@ fn load_rom_rw(path: &str) -> Result<(Rom, File), Error> {
     Ok((rom, file))
 }
 
+fn validate_threads(value: String) -> Result<(), String> {
+    match value.parse::<usize>() {
+        Ok(count) if count > 0 => Ok(()),
+        _ => Err(String::from("Thread count must be a positive integer")),
+    }
+}
+
+/// Ask the user a yes or no question, defaulting to no.
+fn confirm(question: &str) -> Result<bool, Error> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Parse a length, which may be given in hexadecimal with a `0x` prefix.
+fn parse_length(value: &str) -> Result<usize, std::num::ParseIntError> {
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+}
+
+fn validate_length(value: String) -> Result<(), String> {
+    parse_length(&value)
+        .map(|_| ())
+        .map_err(|_| String::from("Length must be a non-negative integer"))
+}
+
+fn validate_address(value: String) -> Result<(), String> {
+    match parse_length(&value) {
+        Ok(address) if address <= u32::MAX as usize => Ok(()),
+        _ => Err(String::from("Address must be a 32-bit integer")),
+    }
+}
+
+fn validate_name(value: String) -> Result<(), String> {
+    if value.len() <= Header::NAME_SIZE {
+        Ok(())
+    } else {
+        Err(format!("Name must be at most {} bytes", Header::NAME_SIZE))
+    }
+}
+
+fn validate_media(value: String) -> Result<(), String> {
+    if value.len() == 4 {
+        Ok(())
+    } else {
+        Err(String::from("Media format must be exactly 4 bytes"))
+    }
+}
+
+/// Report each difference between two rom files, returning whether or not any were found.
+///
+/// Header fields and the CIC are compared as decoded values, and the data region (everything after
+/// the header and IPL3) is compared in big-endian order, so the byte order itself is not a difference.
+fn diff(a: &Rom, b: &Rom) -> bool {
+    let mut fields = Vec::new();
+    let name = |rom: &Rom| format!("{:?}", String::from_utf8_lossy(rom.header.name()));
+    let hex = |value: u32| format!("{:#010X}", value);
+    let media = |rom: &Rom| format!("{:?}", String::from_utf8_lossy(rom.header.media().as_ref()));
+    fields.push(("Name", name(a), name(b)));
+    fields.push(("CRC1", hex(a.header.crcs().0), hex(b.header.crcs().0)));
+    fields.push(("CRC2", hex(a.header.crcs().1), hex(b.header.crcs().1)));
+    fields.push(("Entry Point", hex(a.header.entry_point()), hex(b.header.entry_point())));
+    fields.push(("Clock Rate", hex(a.header.clock_rate()), hex(b.header.clock_rate())));
+    fields.push(("Release", hex(a.header.release()), hex(b.header.release())));
+    fields.push(("Media Format", media(a), media(b)));
+    fields.push(("CIC", a.cic().to_string(), b.cic().to_string()));
+
+    let mut differ = false;
+    for (field, a, b) in fields {
+        if a != b {
+            println!("{}: {} vs {}", field, a, b);
+            differ = true;
+        }
+    }
+    if a.header.magic().as_ref() != b.header.magic().as_ref() {
+        println!("Magic: {:?} vs {:?}", a.header.magic().as_ref(), b.header.magic().as_ref());
+        differ = true;
+    }
+    if a.ipl3.get_ipl()[..] != b.ipl3.get_ipl()[..] && a.cic() == b.cic() {
+        println!("IPL3: Bootcode differs");
+        differ = true;
+    }
+    if a.order() != b.order() {
+        println!("Note: Byte order {} vs {}, which is not compared", a.order(), b.order());
+    }
+
+    // Compare the data region, including any bytes past the end of the shorter one
+    let (data_a, data_b) = (a.data(), b.data());
+    let mismatches = data_a.iter().zip(data_b).enumerate().filter(|(_, (x, y))| x != y);
+    let first = mismatches.clone().next().map(|(idx, _)| idx);
+    let extra = data_a.len().max(data_b.len()) - data_a.len().min(data_b.len());
+    let count = mismatches.count() + extra;
+    if data_a.len() != data_b.len() {
+        println!("Data Length: {:#X} vs {:#X}", data_a.len(), data_b.len());
+    }
+    if count > 0 {
+        let first = first.unwrap_or_else(|| data_a.len().min(data_b.len()));
+        println!("Data: {} bytes differ, first at offset {:#X}", count, HEAD_SIZE + first);
+        differ = true;
+    }
+    differ
+}
+
+/// Size of a 64DD disk image in the common "ndd" format.
+const DISK_SIZE: usize = 0x3DE_C800;
+
+/// Report each finding about a file, reporting "Inconclusive" for checks which cannot be made.
+///
+/// This only fails if the file cannot be read.
+fn detect(path: &str) -> Result<(), Error> {
+    let data = std::fs::read(path)?;
+    println!("File Size: {} bytes", data.len());
+
+    // A copier header would precede the rom data
+    let copier = rom::has_copier_header(&data);
+    if copier {
+        println!("Copier Header: Yes ({} bytes)", rom::COPIER_HEADER_SIZE);
+    } else {
+        println!("Copier Header: No");
+    }
+    let data = if copier { &data[rom::COPIER_HEADER_SIZE..] } else { &data[..] };
+
+    let order = Magic::infer_byte_order(data).ok();
+    match order {
+        Some(order) => println!("Magic: Valid ({})", order),
+        None => println!("Magic: Invalid"),
+    }
+
+    if Magic::infer_kind(data).ok() == Some(MagicKind::Disk) {
+        println!("64DD Disk: Likely (magic matches a disk image)");
+    } else if data.len() == DISK_SIZE {
+        println!("64DD Disk: Likely (size matches a disk image)");
+    } else {
+        println!("64DD Disk: No");
+    }
+
+    // Remaining checks require parsing the rom
+    let rom = match order.map(|_| Rom::read(&mut &data[..])) {
+        Some(Ok(rom)) if rom.len() > HEAD_SIZE => Some(rom),
+        _ => None,
+    };
+    match &rom {
+        Some(rom) => {
+            println!("CIC (Boot Block): {}", rom.cic());
+            match rom.guess_cic() {
+                Some(cic) => println!("CIC (CRC Inference): {}", cic),
+                None => println!("CIC (CRC Inference): Inconclusive"),
+            }
+            let size = rom.len() - rom.overdump_len();
+            if rom.is_truncated() {
+                println!("Likely Size: {} bytes (may be truncated)", size);
+            } else {
+                println!("Likely Size: {} bytes", size);
+            }
+        }
+        None => {
+            println!("CIC (Boot Block): Inconclusive");
+            println!("CIC (CRC Inference): Inconclusive");
+            println!("Likely Size: Inconclusive");
+        }
+    }
+
+    Ok(())
+}
+
+/// Verify the CRC values of a single rom file.
+fn check_rom(path: &str) -> Result<(), Error> {
+    let (result, crcs) = rom::verify_path(path)?;
+    if result {
+        Ok(())
+    } else {
+        Err(Error::CRCError(crcs.0, crcs.1))
+    }
+}
+
+/// Verify the CRC values of multiple rom files using a pool of threads.
+///
+/// Results are returned in the same order as the given paths.
+fn check_roms(paths: &[&str], threads: usize) -> Vec<Result<(), Error>> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(paths.len()) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let idx = next.fetch_add(1, Ordering::SeqCst);
+                match paths.get(idx) {
+                    Some(path) => sender.send((idx, check_rom(path))).unwrap(),
+                    None => break,
+                }
+            });
+        }
+    });
+    drop(sender);
+
+    let mut results: Vec<_> = receiver.into_iter().collect();
+    results.sort_by_key(|(idx, _)| *idx);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Severity used to highlight a line of output.
+#[derive(Clone, Copy)]
+enum Highlight {
+    Error,
+    Warning,
+}
+
+/// Determine whether or not to use color output.
+fn use_color(when: &str) -> bool {
+    match when {
+        "always" => true,
+        "never" => false,
+        // Respect NO_COLOR (https://no-color.org) and only use color when writing to a terminal
+        _ => env::var_os("NO_COLOR").filter(|value| !value.is_empty()).is_none() && io::stdout().is_terminal(),
+    }
+}
+
+/// Print a line, highlighting it if color output is enabled.
+#[cfg(feature = "color")]
+fn print_line(line: &str, highlight: Option<Highlight>, color: bool) -> io::Result<()> {
+    let choice = if color { ColorChoice::Always } else { ColorChoice::Never };
+    let mut stdout = StandardStream::stdout(choice);
+    match highlight {
+        Some(highlight) => {
+            let fg = match highlight {
+                Highlight::Error => Color::Red,
+                Highlight::Warning => Color::Yellow,
+            };
+            stdout.set_color(ColorSpec::new().set_fg(Some(fg)))?;
+            writeln!(stdout, "{}", line)?;
+            stdout.reset()
+        }
+        None => writeln!(stdout, "{}", line),
+    }
+}
+
+/// Print a line, highlighting it if color output is enabled.
+#[cfg(not(feature = "color"))]
+fn print_line(line: &str, _: Option<Highlight>, _: bool) -> io::Result<()> {
+    println!("{}", line);
+    Ok(())
+}
+
+/// Quote a string for JSON output, escaping special and control characters.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Print the fields of a rom as a single JSON object, see the help text of `show --json`.
+fn print_json(rom: &Rom, size: u64) {
+    let name = String::from_utf8_lossy(rom.header.name());
+    let name = name.trim_end_matches(&[' ', '\0'][..]);
+    let byte_order = match rom.order() {
+        Endianness::Big => "big",
+        Endianness::Little => "little",
+        Endianness::Mixed => "mixed",
+    };
+    let (crc1, crc2) = rom.header.crcs();
+    let media = String::from_utf8_lossy(rom.header.media().as_ref());
+    let fields = [
+        format!("\"name\":{}", json_string(name)),
+        format!("\"cic\":{}", json_string(rom.ipl3.short_name())),
+        format!("\"byte_order\":{}", json_string(byte_order)),
+        format!("\"crc1\":{}", crc1),
+        format!("\"crc2\":{}", crc2),
+        format!("\"entry_point\":{}", rom.header.entry_point()),
+        format!("\"media\":{}", json_string(&media)),
+        format!("\"size_bytes\":{}", size),
+    ];
+    println!("{{{}}}", fields.join(","));
+}
+
+/// Print the digests of the full rom image and body.
+#[cfg(feature = "hashes")]
+fn print_hashes(rom: &Rom, color: bool) -> io::Result<()> {
+    let hex = |digest: &[u8]| digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+    print_line(&format!("  MD5: {}", hex(&rom.md5())), None, color)?;
+    print_line(&format!("  SHA1: {}", hex(&rom.sha1())), None, color)?;
+    print_line(&format!("  Body MD5: {}", hex(&rom.body_md5())), None, color)?;
+    print_line(&format!("  Body SHA1: {}", hex(&rom.body_sha1())), None, color)
+}
+
+/// Print the digests of the full rom image and body.
+#[cfg(not(feature = "hashes"))]
+fn print_hashes(_: &Rom, color: bool) -> io::Result<()> {
+    print_line("  Hashes: Unavailable, requires the hashes feature", Some(Highlight::Warning), color)
+}
+
+fn parse_order(value: &str) -> Endianness {
+    match value {
+        "big" => Endianness::Big,
+        "little" => Endianness::Little,
+        "mixed" => Endianness::Mixed,
+        _ => unreachable!(),
+    }
+}
+
 fn main_with_args(matches: &ArgMatches) -> Result<(), Error> {
 
     match matches.subcommand() {
+        ("build", Some(matches)) => {
+            let ipl3 = IPL3::read_path(matches.value_of("ipl3").unwrap())?;
+            let program = fs::read(matches.value_of("program").unwrap())?;
+            let entry_point = parse_length(matches.value_of("entry").unwrap()).unwrap() as u32;
+            let media = matches.value_of("media").map_or(&[0; 4][..], str::as_bytes);
+            let rom = Rom::new(ipl3, entry_point, matches.value_of("name").unwrap(), media, program, &[])?;
+            let output = matches.value_of("out").unwrap();
+            rom.save(output, None)?;
+            println!("Built {} bytes with IPL3: {}", rom.len(), ipl3);
+            Ok(())
+        }
         ("check", Some(matches)) => {
-            let path = matches.value_of("file").unwrap();
-            let (rom, _) = load_rom(&path, true)?;
-
-            let (result, crcs) = rom.check_crc();
-            if result {
+            let paths: Vec<&str> = matches.values_of("file").unwrap().collect();
+            if paths.len() == 1 {
+                // Show the stored and computed values side by side
+                let (rom, _) = load_rom(paths[0], true)?;
+                let report = rom.verify();
+                let ((crc1, crc2), (calc1, calc2)) = (report.stored, report.computed);
+                println!("IPL3: {}", rom.ipl3);
+                println!("Stored:   ({:#010X}, {:#010X})", crc1, crc2);
+                println!("Computed: ({:#010X}, {:#010X})", calc1, calc2);
+                if report.status == VerifyStatus::UnknownCic {
+                    println!("Warning: The CIC is unknown, so the computed CRC values cannot be trusted");
+                }
+                if report.stored != report.computed {
+                    return Err(Error::CRCError(calc1, calc2));
+                }
                 println!("Correct!");
+                return Ok(());
+            }
+
+            let threads = match matches.value_of("threads") {
+                Some(value) => value.parse().unwrap(),
+                None => thread::available_parallelism().map(|count| count.get()).unwrap_or(1),
+            };
+
+            // Report the result of each rom file, in the order given
+            let mut failed = 0;
+            for (path, result) in paths.iter().zip(check_roms(&paths, threads)) {
+                match result {
+                    Ok(()) => println!("{}: Correct!", path),
+                    Err(err) => {
+                        println!("{}: {}", path, err);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if failed == 0 {
                 Ok(())
             } else {
-                Err(Error::CRCError(crcs.0, crcs.1))
+                Err(Error::BatchError(failed, paths.len()))
             }
         }
         ("convert", Some(matches)) => {
             // Get variables from arguments.
             let input = matches.value_of("input").unwrap();
             let output = matches.value_of("output").unwrap();
+            // The auto target converts any byte order to big-endian
             let order = match matches.value_of("order").unwrap() {
-                "big" => Endianness::Big,
-                "little" => Endianness::Little,
-                "mixed" => Endianness::Mixed,
-                _ => unreachable!(),
+                "auto" => Endianness::Big,
+                value => parse_order(value),
             };
-            // Perform rom convert.
-            let (result, _) = convert::convert_rom_path(&input, &output, order)?;
-            if matches!(result, ConvertStatus::AlreadyConverted) {
-                println!("Rom file is already in {} byte order.", order);
-            } else {
+            if matches.is_present("trim") {
+                // Load entire rom to trim it before writing with the chosen byte order.
+                let (mut rom, _) = load_rom(input, true)?;
+                let trimmed = rom.trim_overdump();
+                if trimmed > 0 {
+                    println!("Trimmed {} bytes of overdump data.", trimmed);
+                }
+                rom.save(output, Some(order))?;
                 println!("Done!");
+                return Ok(());
+            }
+
+            // Perform rom convert, using the given input byte order if any.
+            let (result, _) = match matches.value_of("from") {
+                Some(from) => {
+                    let from = parse_order(from);
+                    println!("Warning: Skipped inferring the input byte order, using: {}", from);
+                    convert::convert_rom_path_as(&input, &output, from, order)?
+                }
+                None => convert::convert_rom_path(&input, &output, order)?,
+            };
+            match result {
+                ConvertStatus::AlreadyConverted(_) => println!("Rom file is already in {} byte order.", order),
+                ConvertStatus::Complete(from) => println!("Converted from {} to {}.", from, order),
             }
             Ok(())
         }
         ("correct", Some(matches)) => {
             let path = matches.value_of("file").unwrap();
             let (mut rom, mut file) = load_rom_rw(&path)?;
+            let order = matches.value_of("order").map(parse_order).filter(|&order| order != rom.order());
+
+            if let Some(order) = order {
+                if rom.correct_crc() {
+                    println!("Rom CRC values are already correct!");
+                } else {
+                    println!("Corrected!");
+                }
 
-            if rom.correct_crc() {
+                // Rewrite the full image, which has the same length in any byte order
+                file.seek(SeekFrom::Start(0))?;
+                rom.write(&mut file, Some(&order))?;
+                file.flush()?;
+
+                println!("Converted from {} to {}.", rom.order(), order);
+                Ok(())
+            } else if rom.correct_crc() {
                 println!("Rom CRC values are already correct!");
                 Ok(())
             } else {
@@ -153,27 +794,226 @@ fn main_with_args(matches: &ArgMatches) -> Result<(), Error> {
                 Ok(())
             }
         }
+        ("detect", Some(matches)) => detect(matches.value_of("file").unwrap()),
+        ("diff", Some(matches)) => {
+            let (a, _) = load_rom(matches.value_of("a").unwrap(), true)?;
+            let (b, _) = load_rom(matches.value_of("b").unwrap(), true)?;
+            if diff(&a, &b) {
+                Err(Error::DiffError)
+            } else {
+                println!("Rom contents are identical.");
+                Ok(())
+            }
+        }
+        ("dump", Some(matches)) => {
+            let path = matches.value_of("file").unwrap();
+            let order = parse_order(matches.value_of("order").unwrap());
+            let (offset, length) = match (matches.value_of("offset"), matches.value_of("length")) {
+                (None, None) => (0, Header::SIZE),
+                (offset, length) => (
+                    offset.map(|value| parse_length(value).unwrap()).unwrap_or(0),
+                    length.map(|value| parse_length(value).unwrap()).unwrap_or(usize::MAX),
+                ),
+            };
+
+            let (rom, _) = load_rom(path, true)?;
+            let image = rom.full();
+            let start = std::cmp::min(offset, image.len());
+            let end = std::cmp::min(start.saturating_add(length), image.len());
+
+            // Convert the surrounding aligned words, since conversion swaps within each word
+            let aligned_start = start - (start % 4);
+            let aligned_end = std::cmp::min(end + ((4 - (end % 4)) % 4), image.len() - (image.len() % 4));
+            let data = if order != Endianness::Big && aligned_start < aligned_end {
+                let words = image[aligned_start..aligned_end].to_vec();
+                let mut data = convert::convert_vec(words, Endianness::Big, order)?;
+                data.extend_from_slice(&image[aligned_end..end.max(aligned_end)]);
+                data
+            } else {
+                image[aligned_start..end].to_vec()
+            };
+
+            for line in hexdump_lines(&data[start - aligned_start..end - aligned_start], start) {
+                println!("{}", line);
+            }
+            Ok(())
+        }
+        ("extract", Some(matches)) => {
+            let input = matches.value_of("input").unwrap();
+            let output = matches.value_of("output").unwrap();
+            let written = if matches.is_present("head") {
+                let (rom, _) = load_rom(input, false)?;
+                let order = matches.value_of("order").map(parse_order);
+                let mut out_file = File::create(output)?;
+                rom.write_head(&mut out_file, order.as_ref())?
+            } else if let Some(boot) = matches.value_of("boot") {
+                let boot_len = parse_length(boot).unwrap();
+                let (rom, _) = load_rom(input, true)?;
+                let blob = rom.head_and_boot(boot_len);
+                let mut out_file = File::create(output)?;
+                out_file.write_all(&blob)?;
+                blob.len()
+            } else {
+                // The body is held in big-endian order, whatever the byte order of the rom file
+                let (rom, _) = load_rom(input, true)?;
+                let mut out_file = File::create(output)?;
+                let mut written = 0;
+                if matches.is_present("with-ipl3") {
+                    written += rom.ipl3.write(&mut out_file)?;
+                }
+                out_file.write_all(rom.data())?;
+                written + rom.data().len()
+            };
+
+            println!("Extracted {} bytes.", written);
+            Ok(())
+        }
+        ("extract-ipl3", Some(matches)) => {
+            let (rom, _) = load_rom(matches.value_of("input").unwrap(), false)?;
+            fs::write(matches.value_of("output").unwrap(), rom.ipl3_bytes())?;
+            println!("Extracted {} bootcode.", rom.ipl3);
+            Ok(())
+        }
+        ("normalize", Some(matches)) => {
+            let path = matches.value_of("file").unwrap();
+            let output = matches.value_of("output").unwrap_or(path);
+            let enabled = |name| matches.is_present("fix-all") || matches.is_present(name);
+
+            // Only fall back to the given byte order if the magic number is corrupt
+            let mut file = File::open(path)?;
+            let mut rom = match Rom::read(&mut file) {
+                Ok(rom) => rom,
+                Err(n64rom::header::Error::UnknownByteOrder(_)) if enabled("fix-magic") => {
+                    file.seek(SeekFrom::Start(0))?;
+                    Rom::read_with_order(&mut file, parse_order(matches.value_of("as").unwrap()), true)?
+                }
+                Err(err) => return Err(err.into()),
+            };
+            drop(file);
+
+            let mut changed = false;
+            if enabled("fix-magic") && rom.header.magic().byte_order().is_err() {
+                let question = format!("Rewrite corrupt magic number {} with the canonical value?", rom.header.magic());
+                if matches.is_present("yes") || confirm(&question)? {
+                    rom.fix_magic();
+                    println!("Repaired magic.");
+                    changed = true;
+                } else {
+                    println!("Skipped repairing magic.");
+                }
+            }
+            if enabled("trim") {
+                let trimmed = rom.trim_overdump();
+                if trimmed > 0 {
+                    println!("Trimmed {} bytes of overdump data.", trimmed);
+                    changed = true;
+                }
+            }
+            if enabled("pad") {
+                let padded = rom.pad_to_power_of_two(0);
+                if padded > 0 {
+                    println!("Padded with {} bytes.", padded);
+                    changed = true;
+                }
+            }
+            // Correct the CRC values last, as other actions may change the body
+            if enabled("fix-crc") && !rom.correct_crc() {
+                println!("Corrected CRC values.");
+                changed = true;
+            }
+
+            if changed || output != path {
+                rom.save(output, None)?;
+                println!("Wrote {}", output);
+            } else {
+                println!("Rom file is already normalized.");
+            }
+            Ok(())
+        }
+        ("repair", Some(matches)) => {
+            let path = matches.value_of("file").unwrap();
+            let order = parse_order(matches.value_of("as").unwrap());
+            let in_path = Path::new(path);
+            let mut file = OpenOptions::new().read(true).write(true).open(in_path)?;
+            let mut rom = Rom::read_with_order(&mut file, order, false)?;
+
+            if matches.is_present("magic") {
+                if rom.header.magic().byte_order().is_ok() {
+                    println!("Rom magic is already valid, skipping.");
+                } else {
+                    rom.fix_magic();
+                    println!("Repaired magic.");
+                }
+            }
+
+            file.seek(SeekFrom::Start(0))?;
+
+            // Use a writer that respects the original byte order
+            let mut writer = Writer::from(&mut file, rom.order());
+            rom.header.write(&mut writer)?;
+            writer.flush()?;
+
+            Ok(())
+        }
         ("show", Some(matches)) => {
-            // Read rom with only head (header & IPL3)
+            // Read rom with only head (header & IPL3), unless verifying
             let path = matches.value_of("file").unwrap();
-            let (rom, file) = load_rom(&path, false)?;
+            let color = use_color(matches.value_of("color").unwrap());
+            let verify = matches.is_present("verify");
+            let hashes = matches.is_present("hashes");
+            let (rom, file) = load_rom(&path, verify || hashes)?;
 
             // For efficiency, instead of reading all data to determine rom size, check file metadata
             let metadata = file.metadata()?;
-            let filesize = FileSize::from(metadata.len(), MEBIBYTE);
+            if matches.is_present("json") {
+                print_json(&rom, metadata.len());
+                return Ok(());
+            }
+            let sizetext = FileSize::human(metadata.len());
+
+            let ipl3_highlight = match rom.ipl3.cic() {
+                Cic::Unknown => Some(Highlight::Warning),
+                _ => None,
+            };
+
+            print_line(&rom.header.to_string(), None, color)?;
+            let cartridge_type = rom.header.media().cartridge_type();
+            let cartridge_type = cartridge_type.map_or(String::from("Unknown"), |value| value.to_string());
+            print_line(&format!("  Cartridge Type: {}", cartridge_type), None, color)?;
+            print_line(&format!("  Region: {}", rom.region()), None, color)?;
+            print_line(&format!("  IPL3: {}", rom.ipl3), ipl3_highlight, color)?;
+            print_line(&format!("  Byte Order: {}", rom.order()), None, color)?;
+            print_line(&format!("  Rom Size: {}", &sizetext), None, color)?;
+            print_line(&format!("  ID: {}", rom.goodn64_id()), None, color)?;
 
-            // Show size text in MiB
-            let sizetext = match filesize {
-                FileSize::Float(value) => {
-                    format!("{:.*} MiB", 1, value)
+            if let Some(warning) = rom.media_region_mismatch_warning() {
+                if matches.is_present("strict") {
+                    return Err(Error::WarningError(warning));
                 }
-                FileSize::Int(value) => {
-                    format!("{} MiB", value)
+                print_line(&format!("  Warning: {}", warning), Some(Highlight::Warning), color)?;
+            }
+
+            if verify {
+                if rom.is_truncated() {
+                    let warning = String::from("Rom may be truncated, the dump could be incomplete");
+                    if matches.is_present("strict") {
+                        return Err(Error::WarningError(warning));
+                    }
+                    print_line(&format!("  Warning: {}", warning), Some(Highlight::Warning), color)?;
                 }
-            };
 
-            println!("{}", rom);
-            println!("  Rom Size: {}", &sizetext);
+                let (result, (crc1, crc2)) = rom.check_crc();
+                if result {
+                    print_line("  CRC: Correct", None, color)?;
+                } else {
+                    let line = format!("  CRC: Bad, expected: (0x{:08X}, 0x{:08X})", crc1, crc2);
+                    print_line(&line, Some(Highlight::Error), color)?;
+                }
+            }
+
+            if hashes {
+                print_hashes(&rom, color)?;
+            }
 
             Ok(())
         }
@@ -1,29 +1,59 @@
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
-use crate::header::Magic;
 use crate::rom::{Endianness, Rom};
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Buffer length must be 4-byte aligned to perform conversion, instead found length: {0}")]
     AlignmentError(usize),
-    #[error("During conversion, read {0} bytes but only wrote {1} bytes")]
-    FileWriteError(usize, usize),
     #[error("{0}")]
     HeaderError(#[from] crate::header::Error),
     #[error("{0}")]
     IOError(#[from] io::Error),
+    /// A path could not be resolved to check whether the input and output are the same file.
+    #[error("Unable to resolve path {}: {1}", .0.display())]
+    PathResolveError(PathBuf, io::Error),
 }
 
+/// Size of chunks to convert at once with `convert_stream`.
+const CHUNK_SIZE: usize = 1024 * 64;
+
+// Assert chunk size is divisible by 4.
+const_assert_eq!(CHUNK_SIZE % 4, 0);
+
 /// Perform 4-byte swap between Big Endian and Little Endian.
 fn swap_big_little(buf: &mut [u8]) {
     buf.swap(0, 3);
     buf.swap(1, 2);
 }
 
+/// Perform 4-byte swap between Big Endian and Little Endian on each word of a 4-byte aligned buffer.
+fn swap_words_big_little(buf: &mut [u8]) {
+    #[cfg(feature = "simd")]
+    let buf = simd_swap_big_little(buf);
+    for chunk in buf.chunks_exact_mut(4) {
+        swap_big_little(chunk);
+    }
+}
+
+/// Perform 4-byte swap between Big Endian and Little Endian on 16 bytes at a time, returning the
+/// remaining tail which is too short to be swapped this way.
+#[cfg(feature = "simd")]
+fn simd_swap_big_little(buf: &mut [u8]) -> &mut [u8] {
+    use std::simd::{simd_swizzle, u8x16};
+
+    let mut chunks = buf.chunks_exact_mut(16);
+    for chunk in &mut chunks {
+        let words = u8x16::from_slice(chunk);
+        let swapped = simd_swizzle!(words, [3, 2, 1, 0, 7, 6, 5, 4, 11, 10, 9, 8, 15, 14, 13, 12]);
+        swapped.copy_to_slice(chunk);
+    }
+    chunks.into_remainder()
+}
+
 /// Perform 4-byte swap between Big Endian and Mixed Endian.
 fn swap_big_mixed(buf: &mut [u8]) {
     buf.swap(0, 1);
@@ -36,9 +66,20 @@ fn swap_little_mixed(buf: &mut [u8]) {
     buf.swap(1, 3);
 }
 
+/// Result of a conversion, holding the `Endianness` which the data was converted from.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ConvertStatus {
-    AlreadyConverted,
-    Complete,
+    AlreadyConverted(Endianness),
+    Complete(Endianness),
+}
+
+impl ConvertStatus {
+    /// Get the `Endianness` which the data was converted from.
+    pub fn source(&self) -> Endianness {
+        match *self {
+            Self::AlreadyConverted(order) | Self::Complete(order) => order,
+        }
+    }
 }
 
 trait RomConvert {
@@ -51,21 +92,19 @@ struct BigEndianConverter;
 
 impl RomConvert for BigEndianConverter {
     fn convert_to_big(_: &mut [u8]) -> ConvertStatus {
-        ConvertStatus::AlreadyConverted
+        ConvertStatus::AlreadyConverted(Endianness::Big)
     }
 
     fn convert_to_little(buf: &mut [u8]) -> ConvertStatus {
-        for chunk in buf.chunks_exact_mut(4) {
-            swap_big_little(chunk);
-        }
-        ConvertStatus::Complete
+        swap_words_big_little(buf);
+        ConvertStatus::Complete(Endianness::Big)
     }
 
     fn convert_to_mixed(buf: &mut [u8]) -> ConvertStatus {
         for chunk in buf.chunks_exact_mut(4) {
             swap_big_mixed(chunk);
         }
-        ConvertStatus::Complete
+        ConvertStatus::Complete(Endianness::Big)
     }
 }
 
@@ -73,21 +112,19 @@ struct LittleEndianConverter;
 
 impl RomConvert for LittleEndianConverter {
     fn convert_to_big(buf: &mut [u8]) -> ConvertStatus {
-        for chunk in buf.chunks_exact_mut(4) {
-            swap_big_little(chunk);
-        }
-        ConvertStatus::Complete
+        swap_words_big_little(buf);
+        ConvertStatus::Complete(Endianness::Little)
     }
 
     fn convert_to_little(_: &mut [u8]) -> ConvertStatus {
-        ConvertStatus::AlreadyConverted
+        ConvertStatus::AlreadyConverted(Endianness::Little)
     }
 
     fn convert_to_mixed(buf: &mut [u8]) -> ConvertStatus {
         for chunk in buf.chunks_exact_mut(4) {
             swap_little_mixed(chunk);
         }
-        ConvertStatus::Complete
+        ConvertStatus::Complete(Endianness::Little)
     }
 }
 
@@ -98,18 +135,18 @@ impl RomConvert for MixedEndianConverter {
         for chunk in buf.chunks_exact_mut(4) {
             swap_big_mixed(chunk);
         }
-        ConvertStatus::Complete
+        ConvertStatus::Complete(Endianness::Mixed)
     }
 
     fn convert_to_little(buf: &mut [u8]) -> ConvertStatus {
         for chunk in buf.chunks_exact_mut(4) {
             swap_little_mixed(chunk);
         }
-        ConvertStatus::Complete
+        ConvertStatus::Complete(Endianness::Mixed)
     }
 
     fn convert_to_mixed(_: &mut [u8]) -> ConvertStatus {
-        ConvertStatus::AlreadyConverted
+        ConvertStatus::AlreadyConverted(Endianness::Mixed)
     }
 }
 
@@ -138,6 +175,24 @@ pub fn convert(buf: &mut [u8], current: Endianness, target: Endianness) -> Resul
     }
 }
 
+/// Convert data like `convert`, but leave up to 3 trailing bytes past the last complete word
+/// untouched instead of failing. Returns the amount of trailing bytes which were not converted.
+pub fn convert_lossy(buf: &mut [u8], current: Endianness, target: Endianness) -> (ConvertStatus, usize) {
+    let trailing = buf.len() % 4;
+    let length = buf.len() - trailing;
+    // The prefix is 4-byte aligned, so conversion cannot fail
+    let result = convert(&mut buf[..length], current, target).unwrap();
+    (result, trailing)
+}
+
+/// Convert an owned buffer from the current `Endianness` to a target `Endianness`.
+///
+/// The buffer length must be 4-byte aligned, otherwise `Error::AlignmentError` is returned.
+pub fn convert_vec(mut data: Vec<u8>, current: Endianness, target: Endianness) -> Result<Vec<u8>, Error> {
+    convert(&mut data, current, target)?;
+    Ok(data)
+}
+
 /// Convert `Rom` data to a target `Endianness`.
 pub fn convert_rom(rom: &mut Rom, target: Endianness) -> Result<ConvertStatus, Error> {
     let order = rom.order();
@@ -146,37 +201,302 @@ pub fn convert_rom(rom: &mut Rom, target: Endianness) -> Result<ConvertStatus, E
 
 /// Convenience function to convert a given rom `File` to the specified `Endianness`.
 pub fn convert_rom_file(in_file: &mut File, out_file: &mut File, target: Endianness) -> Result<(ConvertStatus, usize), Error> {
-    // Read first 4 bytes (magic value) to infer endianness.
+    convert_rom_file_with_progress(in_file, out_file, target, |_, _| ())
+}
+
+/// Convert a given rom `File` to the specified `Endianness`, reporting progress.
+///
+/// After each chunk is written, `progress` is called with the amount of bytes written so far and
+/// the total amount of bytes to convert.
+pub fn convert_rom_file_with_progress<F: FnMut(usize, usize)>(in_file: &mut File, out_file: &mut File, target: Endianness, mut progress: F) -> Result<(ConvertStatus, usize), Error> {
+    let order = infer_file_order(in_file)?;
+    let total = (in_file.metadata()?.len() as usize).min(crate::rom::MAX_SIZE);
+
     in_file.seek(SeekFrom::Start(0))?;
-    let mut magic_bytes: [u8; 4] = [0; 4];
-    in_file.read_exact(&mut magic_bytes)?;
-    let order = Magic::infer_byte_order(&magic_bytes)?;
+    let mut handle = in_file.take(crate::rom::MAX_SIZE as u64);
+    let amount = convert_chunks(&mut handle, out_file, order, target, |done| progress(done, total))?;
+    Ok((conversion_status(order, target), amount))
+}
 
-    // Determine filesize in attempt to prevent buffer from re-allocating.
-    let filesize = std::cmp::min(in_file.metadata()?.len(), crate::rom::MAX_SIZE as u64);
-    let mut contents = Vec::with_capacity(filesize as usize);
+/// Infer the `Endianness` of a rom `File` from the magic number at its start.
+fn infer_file_order(in_file: &mut File) -> Result<Endianness, Error> {
+    in_file.seek(SeekFrom::Start(0))?;
+    Ok(Endianness::detect(in_file)?)
+}
 
-    // Read file into memory and perform conversion.
+/// Convert a given rom `File` from a known `Endianness` to the specified `Endianness`.
+///
+/// Unlike `convert_rom_file`, the current byte order is not inferred from the magic number, so
+/// this can convert files with a corrupt or missing header.
+pub fn convert_rom_file_as(in_file: &mut File, out_file: &mut File, current: Endianness, target: Endianness) -> Result<(ConvertStatus, usize), Error> {
     in_file.seek(SeekFrom::Start(0))?;
     let mut handle = in_file.take(crate::rom::MAX_SIZE as u64);
-    let read_amount = handle.read_to_end(&mut contents)?;
+    let amount = convert_stream(&mut handle, out_file, current, target)?;
+    Ok((conversion_status(current, target), amount))
+}
 
-    // Perform endianness conversion.
-    let result = convert(&mut contents, order, target)?;
+/// Get the `ConvertStatus` of a completed conversion between two byte orders.
+fn conversion_status(current: Endianness, target: Endianness) -> ConvertStatus {
+    if current == target {
+        ConvertStatus::AlreadyConverted(current)
+    } else {
+        ConvertStatus::Complete(current)
+    }
+}
 
-    // Write resulting contents to same file.
-    let write_amount = out_file.write(&contents)?;
+/// Convert all data from a reader in the current `Endianness`, writing it to a writer in the
+/// target `Endianness`. Returns the amount of bytes written.
+///
+/// Data is converted in fixed-size chunks instead of being read into memory at once. If the data
+/// length is not 4-byte aligned, `Error::AlignmentError` is returned after all complete words were
+/// written.
+pub fn convert_stream<R: Read, W: Write>(reader: &mut R, writer: &mut W, current: Endianness, target: Endianness) -> Result<usize, Error> {
+    convert_chunks(reader, writer, current, target, |_| ())
+}
 
-    if read_amount == write_amount {
-        Ok((result, read_amount))
+/// Convert data as with `convert_stream`, calling `progress` with the amount of bytes written so
+/// far after each chunk.
+fn convert_chunks<R: Read, W: Write, F: FnMut(usize)>(reader: &mut R, writer: &mut W, current: Endianness, target: Endianness, mut progress: F) -> Result<usize, Error> {
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut carry = 0;
+    let mut total = 0;
+
+    loop {
+        let amount = match reader.read(&mut buffer[carry..]) {
+            Ok(0) => break,
+            Ok(amount) => amount,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+
+        // Convert complete words, and carry a trailing partial word into the next chunk
+        let filled = carry + amount;
+        let length = filled - (filled % 4);
+        convert(&mut buffer[..length], current, target)?;
+        writer.write_all(&buffer[..length])?;
+        buffer.copy_within(length..filled, 0);
+        carry = filled - length;
+        total += length;
+        progress(total);
+    }
+
+    if carry == 0 {
+        Ok(total)
     } else {
-        Err(Error::FileWriteError(read_amount, write_amount))
+        Err(Error::AlignmentError(total + carry))
     }
 }
 
 /// Convenience function to convert a rom file at a given `Path` to the specified `Endianness`.
+///
+/// The input and output may be the same file, see `convert_paths`.
 pub fn convert_rom_path(in_path: impl AsRef<Path>, out_path: impl AsRef<Path>, target: Endianness) -> Result<(ConvertStatus, usize), Error> {
+    convert_paths(in_path.as_ref(), out_path.as_ref(), |in_file, out_file| {
+        convert_rom_file(in_file, out_file, target)
+    })
+}
+
+/// Convenience function to convert a rom file at a given `Path` from a known `Endianness` to the
+/// specified `Endianness`, see `convert_rom_file_as`.
+pub fn convert_rom_path_as(in_path: impl AsRef<Path>, out_path: impl AsRef<Path>, current: Endianness, target: Endianness) -> Result<(ConvertStatus, usize), Error> {
+    convert_paths(in_path.as_ref(), out_path.as_ref(), |in_file, out_file| {
+        convert_rom_file_as(in_file, out_file, current, target)
+    })
+}
+
+/// Convenience function to convert a rom file at a given `Path` to big-endian, whatever its
+/// current `Endianness`. Returns the current `Endianness` inferred from the magic number.
+pub fn normalize_rom_path(in_path: impl AsRef<Path>, out_path: impl AsRef<Path>) -> Result<(ConvertStatus, Endianness), Error> {
+    convert_paths(in_path.as_ref(), out_path.as_ref(), |in_file, out_file| {
+        let order = infer_file_order(in_file)?;
+        let (result, _) = convert_rom_file_as(in_file, out_file, order, Endianness::Big)?;
+        Ok((result, order))
+    })
+}
+
+/// Open the input and output paths and run a conversion between them.
+///
+/// Opening the output truncates it, so if both paths resolve to the same file, the conversion
+/// instead writes to a temporary file next to it, which then replaces the original.
+fn convert_paths<T, F>(in_path: &Path, out_path: &Path, run: F) -> Result<T, Error>
+where
+    F: FnOnce(&mut File, &mut File) -> Result<T, Error>,
+{
     let mut in_file = OpenOptions::new().read(true).open(in_path)?;
-    let mut out_file = OpenOptions::new().write(true).create(true).truncate(true).open(out_path)?;
-    convert_rom_file(&mut in_file, &mut out_file, target)
+    let same_path = match same_file(in_path, out_path)? {
+        Some(path) => path,
+        None => {
+            let mut out_file = OpenOptions::new().write(true).create(true).truncate(true).open(out_path)?;
+            return run(&mut in_file, &mut out_file);
+        }
+    };
+
+    let (temp_path, mut temp_file) = create_temp_file(&same_path)?;
+    let result = run(&mut in_file, &mut temp_file).and_then(|value| {
+        // Keep the permissions of the original, as the temporary file is created with defaults
+        fs::set_permissions(&temp_path, in_file.metadata()?.permissions())?;
+        Ok(value)
+    });
+
+    // Close both files before replacing the original
+    drop(in_file);
+    drop(temp_file);
+    let result = result.and_then(|value| {
+        fs::rename(&temp_path, &same_path)?;
+        Ok(value)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Create a new temporary file in the same directory as a path, so it can later replace it with
+/// a rename. The name is unique to this process, and taken names are skipped.
+fn create_temp_file(path: &Path) -> Result<(PathBuf, File), Error> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut attempt = 0;
+    loop {
+        let temp_path = path.with_file_name(format!(".{}.{}-{}.tmp", name, std::process::id(), attempt));
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+/// Check whether two paths resolve to the same existing file, returning the resolved path if so.
+fn same_file(in_path: &Path, out_path: &Path) -> Result<Option<PathBuf>, Error> {
+    // The output not existing yet is the common case, and it cannot be the input
+    if !out_path.exists() {
+        return Ok(None);
+    }
+    let resolve = |path: &Path| fs::canonicalize(path).map_err(|err| Error::PathResolveError(path.to_path_buf(), err));
+    let in_path = resolve(in_path)?;
+    let out_path = resolve(out_path)?;
+    Ok(if in_path == out_path { Some(out_path) } else { None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const ORDERS: [Endianness; 3] = [Endianness::Big, Endianness::Little, Endianness::Mixed];
+
+    /// Strategy producing 4-byte aligned buffers.
+    fn aligned_buffer() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec(any::<[u8; 4]>(), 0..256)
+            .prop_map(|words| words.concat())
+    }
+
+    /// Strategy producing any byte order.
+    fn order() -> impl Strategy<Value = Endianness> {
+        prop::sample::select(&ORDERS[..])
+    }
+
+    #[test]
+    fn convert_vec_order_pairs() {
+        let expected = |order| match order {
+            Endianness::Big => vec![0x80, 0x37, 0x12, 0x40, 1, 2, 3, 4],
+            Endianness::Little => vec![0x40, 0x12, 0x37, 0x80, 4, 3, 2, 1],
+            Endianness::Mixed => vec![0x37, 0x80, 0x40, 0x12, 2, 1, 4, 3],
+        };
+        for &current in &ORDERS {
+            for &target in &ORDERS {
+                let data = convert_vec(expected(current), current, target).unwrap();
+                assert_eq!(data, expected(target));
+            }
+        }
+    }
+
+    #[test]
+    fn convert_vec_unaligned() {
+        let result = convert_vec(vec![0; 6], Endianness::Big, Endianness::Little);
+        assert!(matches!(result, Err(Error::AlignmentError(6))));
+    }
+
+    #[test]
+    fn convert_stream_multiple_chunks() {
+        let data: Vec<u8> = (0..CHUNK_SIZE * 3 + 8).map(|i| (i * 7 + (i >> 10)) as u8).collect();
+        for &current in &ORDERS {
+            for &target in &ORDERS {
+                let mut reader = io::Cursor::new(&data);
+                let mut output = Vec::new();
+                let amount = convert_stream(&mut reader, &mut output, current, target).unwrap();
+                assert_eq!(amount, data.len());
+                assert_eq!(output, convert_vec(data.clone(), current, target).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn convert_stream_unaligned() {
+        let mut reader = io::Cursor::new(vec![0; 10]);
+        let mut output = Vec::new();
+        let result = convert_stream(&mut reader, &mut output, Endianness::Big, Endianness::Little);
+        assert!(matches!(result, Err(Error::AlignmentError(10))));
+        assert_eq!(output.len(), 8);
+    }
+
+    #[cfg(feature = "simd")]
+    proptest! {
+        #[test]
+        fn simd_matches_scalar(buf in aligned_buffer()) {
+            let mut expected = buf.clone();
+            for chunk in expected.chunks_exact_mut(4) {
+                swap_big_little(chunk);
+            }
+            let mut data = buf;
+            swap_words_big_little(&mut data);
+            prop_assert_eq!(data, expected);
+        }
+    }
+
+    #[test]
+    fn convert_lossy_skips_trailing_bytes() {
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let result = convert_lossy(&mut data, Endianness::Big, Endianness::Little);
+        assert_eq!(result, (ConvertStatus::Complete(Endianness::Big), 2));
+        assert_eq!(data, [4, 3, 2, 1, 8, 7, 6, 5, 9, 10]);
+        assert!(matches!(convert(&mut data, Endianness::Little, Endianness::Big), Err(Error::AlignmentError(10))));
+    }
+
+    proptest! {
+        #[test]
+        fn convert_round_trip(buf in aligned_buffer(), a in order(), b in order()) {
+            let mut data = buf.clone();
+            convert(&mut data, a, b).unwrap();
+            convert(&mut data, b, a).unwrap();
+            prop_assert_eq!(data, buf);
+        }
+
+        #[test]
+        fn swap_helpers_are_consistent(word in any::<[u8; 4]>()) {
+            // Each swap is its own inverse
+            for swap in &[swap_big_little, swap_big_mixed, swap_little_mixed] {
+                let mut data = word;
+                swap(&mut data);
+                swap(&mut data);
+                prop_assert_eq!(data, word);
+            }
+
+            // Converting through big-endian matches converting directly
+            let mut direct = word;
+            swap_little_mixed(&mut direct);
+            let mut indirect = word;
+            swap_big_little(&mut indirect);
+            swap_big_mixed(&mut indirect);
+            prop_assert_eq!(direct, indirect);
+        }
+
+        #[test]
+        fn convert_identity(buf in aligned_buffer(), a in order()) {
+            let mut data = buf.clone();
+            let result = convert(&mut data, a, a).unwrap();
+            prop_assert_eq!(result, ConvertStatus::AlreadyConverted(a));
+            prop_assert_eq!(data, buf);
+        }
+    }
 }
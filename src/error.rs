@@ -0,0 +1,38 @@
+use std::io;
+use thiserror::Error;
+
+/// Crate-level error, unifying the errors of each module.
+///
+/// Each module keeps its own `Error` type for granularity, and each converts into this type, so
+/// callers building on multiple modules can use a single `Result<_, n64rom::Error>`.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{0}")]
+    ConvertError(#[from] crate::convert::Error),
+    #[error("{0}")]
+    HeaderError(#[from] crate::header::Error),
+    #[error("{0}")]
+    IOError(#[from] io::Error),
+    #[error("{0}")]
+    IPL3Error(#[from] crate::ipl3::Error),
+    #[error("{0}")]
+    RomError(#[from] crate::rom::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rom::{Endianness, Rom};
+
+    fn read_and_convert(data: &[u8]) -> Result<Vec<u8>, Error> {
+        let rom = Rom::read(&mut &data[..])?;
+        let converted = crate::convert::convert_vec(rom.image, Endianness::Big, Endianness::Little)?;
+        Ok(converted)
+    }
+
+    #[test]
+    fn module_errors_convert() {
+        let result = read_and_convert(&[0x12, 0x34, 0x56, 0x78]);
+        assert!(matches!(result, Err(Error::HeaderError(_))));
+    }
+}
@@ -1,4 +1,6 @@
 use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
+use std::borrow::Cow;
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::io::{self, Cursor};
 use std::io::prelude::*;
@@ -15,14 +17,41 @@ pub enum Error {
     IOError(#[from] io::Error),
     #[error("Unknown byte order from magic ({0:#08X})")]
     UnknownByteOrder(u32),
+    /// Magic number is not the canonical big-endian magic, and so would be written as-is.
+    #[error("Magic number {0:#010X} is not the canonical {:#010X}", Magic::CANONICAL)]
+    InvalidMagic(u32),
+    /// Too few bytes were given to read a value.
+    #[error("Expected at least {} bytes, found {0}", Magic::SIZE)]
+    InsufficientBytes(usize),
+    /// Too few bytes were given to read a header.
+    #[error("Header must be at least {} bytes, found {0}", Header::SIZE)]
+    HeaderLength(usize),
+    /// Media format is not exactly 4 bytes.
+    #[error("Media format must be 4 bytes, found {0}")]
+    MediaLength(usize),
+    /// A required field was not provided to `HeaderBuilder`.
+    #[error("Missing required header field: {0}")]
+    MissingField(&'static str),
+    /// Rom name is longer than the name field.
+    #[error("Rom name is {0} bytes long, but must be at most {} bytes", Header::NAME_SIZE)]
+    NameTooLong(usize),
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
 /// Represents the initial four bytes of the rom header.
 ///
 /// This value is often used to infer the byte order of the rom data.
 pub struct Magic([u8; 4]);
 
+/// Kind of image identified by a magic number, see `Magic::infer_kind`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MagicKind {
+    /// Cartridge rom, in the given byte order.
+    Cartridge(Endianness),
+    /// 64DD disk image.
+    Disk,
+}
+
 impl fmt::Display for Magic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let order = self.byte_order();
@@ -40,6 +69,10 @@ impl fmt::Display for Magic {
 
 impl Magic {
     pub const SIZE: usize = 4;
+    /// Canonical magic number value.
+    pub const CANONICAL: u32 = 0x8037_1240;
+    /// Known leading values of 64DD disk images, which have no cartridge header.
+    pub const DISK_MAGICS: [u32; 2] = [0xE848_D316, 0x2263_EE56];
 
     /// Infer the byte order (endianness) of the following data.
     pub fn byte_order(&self) -> Result<Endianness, Error> {
@@ -67,6 +100,10 @@ impl Magic {
     }
 
     /// Construct using at least 4 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than 4 bytes are given, see `try_from` for a non-panicking alternative.
     pub fn from(bytes: &[u8]) -> Self {
         // Cleaner way to do this?
         let mut magic = Magic::new();
@@ -74,17 +111,48 @@ impl Magic {
         magic
     }
 
+    /// Construct using at least 4 bytes, returning `Error::InsufficientBytes` if fewer are given.
+    pub fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < Self::SIZE {
+            return Err(Error::InsufficientBytes(bytes.len()));
+        }
+        Ok(Self::from(bytes))
+    }
+
+    /// Infer the byte order from the magic number at the start of the data.
+    ///
+    /// Returns `Error::InsufficientBytes` if the data is shorter than a magic number.
     pub fn infer_byte_order(data: &[u8]) -> Result<Endianness, Error> {
+        if data.len() < Self::SIZE {
+            return Err(Error::InsufficientBytes(data.len()));
+        }
         let value = BigEndian::read_u32(data);
         match value {
-            0x8037_1240 => Ok(Endianness::Big),
+            Magic::CANONICAL => Ok(Endianness::Big),
             0x4012_3780 => Ok(Endianness::Little),
             0x3780_4012 => Ok(Endianness::Mixed),
             _ => Err(Error::UnknownByteOrder(value)),
         }
     }
 
-    pub fn new() -> Self {
+    /// Infer the kind of image from the magic number at the start of the data.
+    ///
+    /// Besides the cartridge magic numbers recognized by `infer_byte_order`, this recognizes the
+    /// leading bytes of the known 64DD disk images, see `DISK_MAGICS`.
+    pub fn infer_kind(data: &[u8]) -> Result<MagicKind, Error> {
+        match Magic::infer_byte_order(data) {
+            Ok(order) => Ok(MagicKind::Cartridge(order)),
+            Err(Error::UnknownByteOrder(value)) if Magic::DISK_MAGICS.contains(&value) => Ok(MagicKind::Disk),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Infer the kind of image from this magic number.
+    pub fn kind(&self) -> Result<MagicKind, Error> {
+        Magic::infer_kind(&self.0)
+    }
+
+    pub const fn new() -> Self {
         Self([128, 55, 18, 64])
     }
 
@@ -107,10 +175,62 @@ impl AsRef<[u8; 4]> for Magic {
 }
 
 /// Media format of rom.
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
 pub struct Media([u8; 4]);
 
+/// Cartridge type, decoded from the first byte of the media format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CartridgeType {
+    /// Retail cartridge (`N`).
+    Cartridge,
+    /// 64DD disk (`D`).
+    Disk,
+    /// Cartridge expandable by a 64DD disk (`C`).
+    ExpandableCartridge,
+    /// 64DD expansion disk for a cartridge (`E`).
+    DiskExpansion,
+    /// Aleck64 arcade board (`Z`).
+    Aleck64,
+}
+
+impl fmt::Display for CartridgeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Cartridge => "Cartridge",
+            Self::Disk => "64DD Disk",
+            Self::ExpandableCartridge => "Expandable Cartridge",
+            Self::DiskExpansion => "64DD Expansion Disk",
+            Self::Aleck64 => "Aleck64",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl CartridgeType {
+    /// Decode from a media format code byte.
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            b'N' => Some(Self::Cartridge),
+            b'D' => Some(Self::Disk),
+            b'C' => Some(Self::ExpandableCartridge),
+            b'E' => Some(Self::DiskExpansion),
+            b'Z' => Some(Self::Aleck64),
+            _ => None,
+        }
+    }
+}
+
 impl Media {
+    /// Construct from a cartridge type code, 2-byte game id and country code.
+    pub fn new(code: u8, id: &[u8; 2], country: u8) -> Self {
+        Self([code, id[0], id[1], country])
+    }
+
+    /// Get the cartridge type decoded from the first byte, if known.
+    pub fn cartridge_type(&self) -> Option<CartridgeType> {
+        CartridgeType::from_code(self.0[0])
+    }
+
     /// Get slice as string.
     pub fn as_str(&self) -> Result<&str, Utf8Error> {
         str::from_utf8(&self.0)
@@ -139,14 +259,135 @@ impl AsRef<[u8; 4]> for Media {
     }
 }
 
-#[derive(Clone, Copy, Default)]
+/// Text encoding of the rom name.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NameEncoding {
+    /// ASCII, with other bytes replaced.
+    Ascii,
+    /// UTF-8, with invalid sequences replaced.
+    Utf8,
+    /// Shift-JIS, used by Japanese retail roms.
+    #[cfg(feature = "encoding_rs")]
+    ShiftJis,
+}
+
+/// Destination region, decoded from the final byte of the media format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Region {
+    Beta,
+    Asia,
+    Brazil,
+    China,
+    Germany,
+    NorthAmerica,
+    France,
+    GatewayNtsc,
+    Netherlands,
+    Italy,
+    Japan,
+    Korea,
+    GatewayPal,
+    Canada,
+    Europe,
+    Spain,
+    Australia,
+    Scandinavia,
+    Unknown(char),
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl Region {
+    /// Get the region name, such as "Japan".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Beta => "Beta",
+            Self::Asia => "Asia (NTSC)",
+            Self::Brazil => "Brazil",
+            Self::China => "China",
+            Self::Germany => "Germany",
+            Self::NorthAmerica => "North America",
+            Self::France => "France",
+            Self::GatewayNtsc => "Gateway 64 (NTSC)",
+            Self::Netherlands => "Netherlands",
+            Self::Italy => "Italy",
+            Self::Japan => "Japan",
+            Self::Korea => "Korea",
+            Self::GatewayPal => "Gateway 64 (PAL)",
+            Self::Canada => "Canada",
+            Self::Europe => "Europe",
+            Self::Spain => "Spain",
+            Self::Australia => "Australia",
+            Self::Scandinavia => "Scandinavia",
+            Self::Unknown(_) => "Unknown",
+        }
+    }
+
+    /// Get the short name used in rom naming conventions, such as "USA" for `Region::NorthAmerica`.
+    /// Returns `None` for `Region::Unknown`.
+    pub fn short_name(&self) -> Option<&'static str> {
+        let name = match self {
+            Self::Beta => "Beta",
+            Self::Asia => "Asia",
+            Self::Brazil => "Brazil",
+            Self::China => "China",
+            Self::Germany => "Germany",
+            Self::NorthAmerica => "USA",
+            Self::France => "France",
+            Self::GatewayNtsc => "Gateway 64 (NTSC)",
+            Self::Netherlands => "Netherlands",
+            Self::Italy => "Italy",
+            Self::Japan => "Japan",
+            Self::Korea => "Korea",
+            Self::GatewayPal => "Gateway 64 (PAL)",
+            Self::Canada => "Canada",
+            Self::Europe => "Europe",
+            Self::Spain => "Spain",
+            Self::Australia => "Australia",
+            Self::Scandinavia => "Scandinavia",
+            Self::Unknown(_) => return None,
+        };
+        Some(name)
+    }
+
+    /// Decode from a region code character.
+    pub fn from_code(code: char) -> Self {
+        match code {
+            '7' => Self::Beta,
+            'A' => Self::Asia,
+            'B' => Self::Brazil,
+            'C' => Self::China,
+            'D' => Self::Germany,
+            'E' => Self::NorthAmerica,
+            'F' => Self::France,
+            'G' => Self::GatewayNtsc,
+            'H' => Self::Netherlands,
+            'I' => Self::Italy,
+            'J' => Self::Japan,
+            'K' => Self::Korea,
+            'L' => Self::GatewayPal,
+            'N' => Self::Canada,
+            'P' | 'X' | 'Y' | 'Z' => Self::Europe,
+            'S' => Self::Spain,
+            'U' => Self::Australia,
+            'W' => Self::Scandinavia,
+            _ => Self::Unknown(code),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Header {
     // Magic number and PI registers.
-    magic: Magic,
+    crate magic: Magic,
     /// Unused by IPL and OS.
     clock_rate: u32,
     /// Executable start address/entry point.
-    entry_point: u32,
+    crate entry_point: u32,
     /// Unused by IPL and OS.
     release: u32,
     /// First CRC value.
@@ -162,6 +403,28 @@ pub struct Header {
     _reserved_3: u8,
 }
 
+assert_impl_all!(Header: Send, Sync);
+
+impl Default for Header {
+    /// Create with the canonical magic number and retail clock rate, and all other fields zeroed.
+    fn default() -> Self {
+        Self::template(0, [0; 20], [0; 4])
+    }
+}
+
+impl TryFrom<&[u8]> for Header {
+    type Error = Error;
+
+    /// Read from the start of a big-endian slice, ignoring any bytes after the header.
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() < Header::SIZE {
+            return Err(Error::HeaderLength(data.len()));
+        }
+        let mut cursor = Cursor::new(data);
+        Ok(Self::read(&mut cursor)?)
+    }
+}
+
 impl fmt::Display for Header {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = self.name_str().unwrap_or("<???>").trim();
@@ -174,12 +437,72 @@ impl fmt::Display for Header {
 
 impl Header {
     pub const SIZE: usize = 0x40;
+    /// Size of the rom name field.
+    pub const NAME_SIZE: usize = 20;
+
+    /// Get the clock rate override value.
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    /// Set the clock rate override value.
+    pub fn set_clock_rate(&mut self, clock_rate: u32) {
+        self.clock_rate = clock_rate;
+    }
+
+    /// Get the raw entry point value, which includes any IPL3 offset.
+    pub fn entry_point(&self) -> u32 {
+        self.entry_point
+    }
+
+    /// Set the raw entry point value.
+    ///
+    /// Unlike `new`, the IPL3 offset is not applied, see `Rom::set_entry_point` for that.
+    pub fn set_entry_point(&mut self, entry_point: u32) {
+        self.entry_point = entry_point;
+    }
+
+    /// Get the release (libultra version) value.
+    pub fn release(&self) -> u32 {
+        self.release
+    }
+
+    /// Set the release (libultra version) value.
+    pub fn set_release(&mut self, release: u32) {
+        self.release = release;
+    }
 
     /// Get CRC values.
     pub fn crcs(&self) -> (u32, u32) {
         (self.crc1, self.crc2)
     }
 
+    /// Get the country code, the final byte of the media format.
+    ///
+    /// This is the raw byte of `region_code`, which decodes it as a character.
+    pub fn country_code(&self) -> u8 {
+        self.media.0[3]
+    }
+
+    /// Get the short country name used in rom naming conventions from the country code, such as
+    /// "USA" for 0x45 or "Europe" for 0x50, if known. See `Region::short_name`.
+    pub fn country(&self) -> Option<&'static str> {
+        self.region().short_name()
+    }
+
+    /// Get the game code, the full 4-character media format (such as "NSME").
+    ///
+    /// This consists of the media format byte, the 2-character game id and the country code. The
+    /// code is ASCII in retail roms, so if the field is not valid UTF-8 only the valid prefix is
+    /// returned, see `Media::as_str` to detect this.
+    pub fn game_code(&self) -> &str {
+        let bytes = self.media.as_ref();
+        match str::from_utf8(bytes) {
+            Ok(code) => code,
+            Err(err) => str::from_utf8(&bytes[..err.valid_up_to()]).unwrap(),
+        }
+    }
+
     /// Get magic number field.
     pub fn magic(&self) -> &Magic {
         &self.magic
@@ -190,6 +513,19 @@ impl Header {
         &self.media
     }
 
+    /// Set media format field, such as to change the region code.
+    pub fn set_media(&mut self, media: &[u8; 4]) {
+        self.media.as_mut().copy_from_slice(media);
+    }
+
+    /// Set media format field like `set_media`, returning `Error::MediaLength` unless the slice is
+    /// exactly 4 bytes.
+    pub fn try_set_media(&mut self, media: &[u8]) -> Result<(), Error> {
+        let media: &[u8; 4] = media.try_into().map_err(|_| Error::MediaLength(media.len()))?;
+        self.set_media(media);
+        Ok(())
+    }
+
     /// Get rom name as bytes.
     pub fn name(&self) -> &[u8; 20] {
         &self.name
@@ -200,20 +536,95 @@ impl Header {
         str::from_utf8(&self.name)
     }
 
-    /// Create a new `Header`.
-    pub fn new(entry_point: u32, name: &str, media: &[u8], program: &[u8], fs: &[u8], ipl3: &IPL3) -> Self {
-        let mut header = Self::default();
-        let (crc1, crc2) = ipl3.compute_crcs(program, fs);
-        let name_bytes = &name.as_bytes()[..20];
-        header.magic = Magic::new();
-        header.clock_rate = 15;
-        header.entry_point = ipl3.offset(entry_point);
-        header.release = 0;
-        header.crc1 = crc1;
-        header.crc2 = crc2;
-        header.name.copy_from_slice(name_bytes);
-        header.media.as_mut().copy_from_slice(&media[..4]);
-        header
+    /// Set the rom name, padding names shorter than the name field with spaces.
+    ///
+    /// Returns `Error::NameTooLong` if the name is longer than `Header::NAME_SIZE` bytes, rather
+    /// than truncating it (which could split a UTF-8 sequence).
+    pub fn set_name(&mut self, name: &str) -> Result<(), Error> {
+        let bytes = name.as_bytes();
+        if bytes.len() > Self::NAME_SIZE {
+            return Err(Error::NameTooLong(bytes.len()));
+        }
+        self.name = [b' '; Self::NAME_SIZE];
+        self.name[..bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Get rom name decoded with the given encoding, with trailing padding (spaces and NUL bytes)
+    /// removed. Bytes which cannot be decoded are replaced with U+FFFD.
+    pub fn name_decoded(&self, encoding: NameEncoding) -> Cow<'_, str> {
+        let decoded = match encoding {
+            NameEncoding::Ascii => {
+                if self.name.is_ascii() {
+                    // An ASCII name is also valid UTF-8
+                    String::from_utf8_lossy(&self.name)
+                } else {
+                    let name = self.name.iter().map(|&b| if b.is_ascii() { b as char } else { '\u{FFFD}' });
+                    Cow::Owned(name.collect())
+                }
+            }
+            NameEncoding::Utf8 => String::from_utf8_lossy(&self.name),
+            #[cfg(feature = "encoding_rs")]
+            NameEncoding::ShiftJis => encoding_rs::SHIFT_JIS.decode_without_bom_handling(&self.name).0,
+        };
+        let trim = |c: char| c == ' ' || c == '\0';
+        match decoded {
+            Cow::Borrowed(name) => Cow::Borrowed(name.trim_end_matches(trim)),
+            Cow::Owned(name) => Cow::Owned(name.trim_end_matches(trim).to_owned()),
+        }
+    }
+
+    /// Create a new `Header`, computing the CRC values from the program and filesystem data.
+    ///
+    /// The name is padded as with `set_name`, and the media format must be 4 bytes. See
+    /// `HeaderBuilder` for the same with named fields.
+    pub fn new(entry_point: u32, name: &str, media: &[u8], program: &[u8], fs: &[u8], ipl3: &IPL3) -> Result<Self, Error> {
+        let media: &[u8; 4] = media.try_into().map_err(|_| Error::MediaLength(media.len()))?;
+        HeaderBuilder::new()
+            .entry_point(entry_point)
+            .name(name)
+            .media(media)
+            .ipl3(ipl3)
+            .program(program)
+            .fs(fs)
+            .build()
+    }
+
+    /// Create a header template from literal fields, usable in a `const` context.
+    ///
+    /// Unlike `new`, the entry point is stored as given without an IPL3 offset, and the CRC values
+    /// are zeroed since they require the program data. Use `with_crcs` to provide them.
+    pub const fn template(entry_point: u32, name: [u8; 20], media: [u8; 4]) -> Self {
+        Self {
+            magic: Magic::new(),
+            clock_rate: 15,
+            entry_point,
+            release: 0,
+            crc1: 0,
+            crc2: 0,
+            _reserved_1: [0; 8],
+            name,
+            _reserved_2: [0; 7],
+            media: Media(media),
+            _reserved_3: 0,
+        }
+    }
+
+    /// Get a copy with the given CRC values, usable in a `const` context.
+    pub const fn with_crcs(mut self, crc1: u32, crc2: u32) -> Self {
+        self.crc1 = crc1;
+        self.crc2 = crc2;
+        self
+    }
+
+    /// Get the destination region decoded from the media format.
+    pub fn region(&self) -> Region {
+        Region::from_code(self.region_code())
+    }
+
+    /// Get the region code character, which is the final byte of the media format.
+    pub fn region_code(&self) -> char {
+        self.country_code() as char
     }
 
     /// Read ordered by converting to big endian.
@@ -222,12 +633,24 @@ impl Header {
         reader.read_exact(&mut buf)?;
         // Infer byte order and convert buffer to big endian.
         let order = Magic::infer_byte_order(&buf)?;
+        let header = Self::read_buffer_as(buf, order)?;
+        Ok((header, order))
+    }
+
+    /// Read using a known byte order, without inferring it from the magic.
+    pub fn read_with_order<T: Read>(reader: &'_ mut T, order: Endianness) -> io::Result<Self> {
+        let mut buf = [0; Header::SIZE];
+        reader.read_exact(&mut buf)?;
+        Self::read_buffer_as(buf, order)
+    }
+
+    /// Read from a buffer after converting it from the given byte order to big endian.
+    fn read_buffer_as(mut buf: [u8; Header::SIZE], order: Endianness) -> io::Result<Self> {
         convert::convert(&mut buf, order, Endianness::Big).unwrap();
         let buf = buf;
         // Read Header from buffer.
         let mut cursor = Cursor::new(&buf);
-        let header = Self::read(&mut cursor)?;
-        Ok((header, order))
+        Self::read(&mut cursor)
     }
 
     /// Read without checking for endianness.
@@ -247,6 +670,18 @@ impl Header {
         Ok(header)
     }
 
+    /// Read like `read`, but return an error if the magic number is not a valid N64 magic.
+    pub fn read_validated<T: Read>(reader: &mut T) -> Result<Self, Error> {
+        let header = Self::read(reader)?;
+        header.validate()?;
+        Ok(header)
+    }
+
+    /// Check that the magic number is a valid N64 magic, in any byte order.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.magic.byte_order().map(|_| ())
+    }
+
     pub fn write<T: Write>(&self, writer: &'_ mut T) -> io::Result<usize> {
         writer.write_all(self.magic.as_ref())?;
         writer.write_u32::<BigEndian>(self.clock_rate)?;
@@ -261,4 +696,393 @@ impl Header {
         writer.write_u8(self._reserved_3)?;
         Ok(Header::SIZE)
     }
+
+    /// Write after validating the header, so a malformed header is not silently written.
+    ///
+    /// This currently requires the canonical magic number. The name and media format are fixed-size
+    /// fields, and so are always written with the correct size.
+    pub fn write_validated<T: Write>(&self, writer: &'_ mut T) -> Result<usize, Error> {
+        let magic = self.magic.to_u32();
+        if magic != Magic::CANONICAL {
+            return Err(Error::InvalidMagic(magic));
+        }
+        Ok(self.write(writer)?)
+    }
+}
+
+/// Serialized form of `Header`, with fields in readable forms and the reserved fields skipped.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, serde::Serialize)]
+struct HeaderRepr {
+    name: String,
+    magic: u32,
+    clock_rate: u32,
+    entry_point: u32,
+    release: u32,
+    crc1: String,
+    crc2: String,
+    media: String,
+}
+
+/// Serializes the name as a trimmed string, the magic number as a `u32`, the CRC values as
+/// hexadecimal strings (such as "0x635A2BFF") and the media format as a 4-character string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Header {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (format, id1, id2, country) = self.media.chars();
+        let repr = HeaderRepr {
+            name: String::from_utf8_lossy(&self.name).trim_end_matches(&[' ', '\0'][..]).to_owned(),
+            magic: self.magic.to_u32(),
+            clock_rate: self.clock_rate,
+            entry_point: self.entry_point,
+            release: self.release,
+            crc1: format!("0x{:08X}", self.crc1),
+            crc2: format!("0x{:08X}", self.crc2),
+            media: [format, id1, id2, country].iter().collect(),
+        };
+        repr.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Header {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        let repr = HeaderRepr::deserialize(deserializer)?;
+        let crc = |value: &str| {
+            let digits = value.strip_prefix("0x").unwrap_or(value);
+            u32::from_str_radix(digits, 16).map_err(D::Error::custom)
+        };
+        let mut media = [0; 4];
+        let chars: Vec<char> = repr.media.chars().collect();
+        if chars.len() != 4 || chars.iter().any(|&c| (c as u32) > 0xFF) {
+            return Err(D::Error::custom("media format must be 4 single-byte characters"));
+        }
+        for (byte, c) in media.iter_mut().zip(chars) {
+            *byte = c as u8;
+        }
+
+        let mut header = Header::template(repr.entry_point, [0; 20], media).with_crcs(crc(&repr.crc1)?, crc(&repr.crc2)?);
+        header.magic = Magic::from(&repr.magic.to_be_bytes());
+        header.clock_rate = repr.clock_rate;
+        header.release = repr.release;
+        header.set_name(&repr.name).map_err(D::Error::custom)?;
+        Ok(header)
+    }
+}
+
+/// Builder for constructing a `Header`, an alternative to the positional arguments of `Header::new`.
+///
+/// The clock rate and release default to retail values. The IPL3 and program data are required,
+/// as the CRC values are computed from them when building.
+#[derive(Clone, Copy, Default)]
+pub struct HeaderBuilder<'a> {
+    entry_point: u32,
+    name: &'a str,
+    media: [u8; 4],
+    ipl3: Option<&'a IPL3>,
+    program: Option<&'a [u8]>,
+    fs: &'a [u8],
+}
+
+impl<'a> HeaderBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the program entry point, which is offset for the IPL3 when building.
+    pub fn entry_point(mut self, entry_point: u32) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+
+    /// Set the rom name, see `Header::set_name`.
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn media(mut self, media: &[u8; 4]) -> Self {
+        self.media = *media;
+        self
+    }
+
+    pub fn ipl3(mut self, ipl3: &'a IPL3) -> Self {
+        self.ipl3 = Some(ipl3);
+        self
+    }
+
+    pub fn program(mut self, program: &'a [u8]) -> Self {
+        self.program = Some(program);
+        self
+    }
+
+    /// Set filesystem data following the program, see `IPL3::compute_crcs`.
+    pub fn fs(mut self, fs: &'a [u8]) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Build the header, computing the CRC values.
+    pub fn build(self) -> Result<Header, Error> {
+        let ipl3 = self.ipl3.ok_or(Error::MissingField("ipl3"))?;
+        let program = self.program.ok_or(Error::MissingField("program"))?;
+        let (crc1, crc2) = ipl3.compute_crcs(program, self.fs);
+        let mut header = Header::template(ipl3.offset(self.entry_point), [0; 20], self.media).with_crcs(crc1, crc2);
+        header.set_name(self.name)?;
+        Ok(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipl3::Cic;
+
+    #[test]
+    fn default_header_is_valid() {
+        let header = Header::default();
+        assert_eq!(header.magic().byte_order().unwrap(), Endianness::Big);
+        assert_eq!(header.clock_rate, 15);
+        assert_eq!(header.crcs(), (0, 0));
+        assert_eq!(header.name(), &[0; 20]);
+    }
+
+    #[test]
+    fn const_template() {
+        const HEADER: Header = Header::template(0x8000_0400, *b"CONST HEADER        ", *b"NCHE")
+            .with_crcs(0x1234_5678, 0x9abc_def0);
+        assert_eq!(HEADER.magic().byte_order().unwrap(), Endianness::Big);
+        assert_eq!(HEADER.entry_point, 0x8000_0400);
+        assert_eq!(HEADER.crcs(), (0x1234_5678, 0x9abc_def0));
+        assert_eq!(HEADER.name_str().unwrap(), "CONST HEADER        ");
+        assert_eq!(HEADER.media().as_str().unwrap(), "NCHE");
+    }
+
+    #[test]
+    fn write_validated_checks_magic() {
+        let mut header = Header::default();
+        let mut output = Vec::new();
+        assert_eq!(header.write_validated(&mut output).unwrap(), Header::SIZE);
+
+        header.magic = Magic::from(&[0x40, 0x12, 0x37, 0x80]);
+        let mut output = Vec::new();
+        assert!(matches!(header.write_validated(&mut output), Err(Error::InvalidMagic(0x4012_3780))));
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn set_name_pads_and_validates() {
+        let mut header = Header::default();
+        header.set_name("ABC").unwrap();
+        assert_eq!(header.name(), b"ABC                 ");
+
+        header.set_name("TWENTY CHARACTERS!!!").unwrap();
+        assert_eq!(header.name_str().unwrap(), "TWENTY CHARACTERS!!!");
+
+        let err = header.set_name("TWENTY ONE CHARACTERS").unwrap_err();
+        assert!(matches!(err, Error::NameTooLong(21)));
+        assert_eq!(header.name_str().unwrap(), "TWENTY CHARACTERS!!!");
+    }
+
+    #[test]
+    fn game_and_country_codes() {
+        // Header of Super Mario 64 (USA)
+        let mut bytes = vec![
+            0x80, 0x37, 0x12, 0x40, 0x00, 0x00, 0x00, 0x0F, 0x80, 0x24, 0x60, 0x00, 0x00, 0x00, 0x14, 0x44,
+            0x63, 0x5A, 0x2B, 0xFF, 0x8B, 0x02, 0x23, 0x26, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        bytes.extend(b"SUPER MARIO 64      ");
+        bytes.extend(&[0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend(b"NSME");
+        bytes.push(0);
+        let header = Header::read(&mut &bytes[..]).unwrap();
+
+        assert_eq!(header.name_str().unwrap(), "SUPER MARIO 64      ");
+        assert_eq!(header.game_code(), "NSME");
+        assert_eq!(header.country_code(), 0x45);
+        assert_eq!(header.country(), Some("USA"));
+        assert_eq!(header.crcs(), (0x635A_2BFF, 0x8B02_2326));
+
+        let mut header = header;
+        header.media.0[3] = 0x4A;
+        assert_eq!(header.country(), Some("Japan"));
+        header.media.0[3] = 0x50;
+        assert_eq!(header.country(), Some("Europe"));
+        header.media.0[3] = b'L';
+        assert_eq!(header.country(), Some("Gateway 64 (PAL)"));
+        header.media.0[3] = 0x00;
+        assert_eq!(header.country(), None);
+        header.media.0[2] = 0xFF;
+        assert_eq!(header.game_code(), "NS");
+    }
+
+    #[test]
+    fn name_decoded_encodings() {
+        let mut header = Header::default();
+        header.set_name("N64ROM TEST").unwrap();
+        assert_eq!(header.name_decoded(NameEncoding::Ascii), "N64ROM TEST");
+        assert_eq!(header.name_decoded(NameEncoding::Utf8), "N64ROM TEST");
+
+        header.name[0] = 0xFF;
+        assert_eq!(header.name_decoded(NameEncoding::Ascii), "\u{FFFD}64ROM TEST");
+    }
+
+    #[test]
+    #[cfg(feature = "encoding_rs")]
+    fn name_decoded_shift_jis() {
+        // "マリオ" in Shift-JIS, padded with NUL bytes
+        let mut header = Header::default();
+        header.name[..6].copy_from_slice(&[0x83, 0x7D, 0x83, 0x8A, 0x83, 0x49]);
+        assert!(header.name_str().is_err());
+        assert_eq!(header.name_decoded(NameEncoding::ShiftJis), "マリオ");
+    }
+
+    #[test]
+    fn new_validates_name_and_media() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6102, [0; crate::ipl3::IPL_SIZE]);
+        let header = Header::new(0x8000_0400, "ABC", b"NABE", &[], &[], &ipl3).unwrap();
+        assert_eq!(header.name(), b"ABC                 ");
+        assert_eq!(header.media().as_str().unwrap(), "NABE");
+
+        let result = Header::new(0x8000_0400, "TWENTY ONE CHARACTERS", b"NABE", &[], &[], &ipl3);
+        assert!(matches!(result, Err(Error::NameTooLong(21))));
+        let result = Header::new(0x8000_0400, "ABC", b"NAB", &[], &[], &ipl3);
+        assert!(matches!(result, Err(Error::MediaLength(3))));
+    }
+
+    #[test]
+    fn header_builder() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6103, [0; crate::ipl3::IPL_SIZE]);
+        let program = [0x5A; 0x100];
+        let header = HeaderBuilder::new()
+            .entry_point(0x8000_0400)
+            .name("MY GAME")
+            .media(b"N\0\0\0")
+            .ipl3(&ipl3)
+            .program(&program)
+            .build()
+            .unwrap();
+        assert!(header == Header::new(0x8000_0400, "MY GAME", b"N\0\0\0", &program, &[], &ipl3).unwrap());
+        assert_eq!(header.entry_point, 0x8010_0400);
+
+        let result = HeaderBuilder::new().program(&program).build();
+        assert!(matches!(result, Err(Error::MissingField("ipl3"))));
+        let result = HeaderBuilder::new().ipl3(&ipl3).build();
+        assert!(matches!(result, Err(Error::MissingField("program"))));
+    }
+
+    #[test]
+    fn validate_magic() {
+        let bytes: Vec<u8> = (0..Header::SIZE).map(|i| (i * 151 + 7) as u8).collect();
+        let header = Header::read(&mut &bytes[..]).unwrap();
+        assert!(matches!(header.validate(), Err(Error::UnknownByteOrder(_))));
+        assert!(matches!(Header::read_validated(&mut &bytes[..]), Err(Error::UnknownByteOrder(_))));
+
+        let mut output = Vec::new();
+        Header::default().write(&mut output).unwrap();
+        assert!(Header::read_validated(&mut &output[..]).is_ok());
+    }
+
+    #[test]
+    fn field_setters_round_trip() {
+        let mut header = Header::default();
+        header.set_clock_rate(0x0000_000E);
+        header.set_entry_point(0x8010_0400);
+        header.set_release(0x0000_144B);
+
+        let mut output = Vec::new();
+        header.write(&mut output).unwrap();
+        let header = Header::read(&mut &output[..]).unwrap();
+        assert_eq!(header.clock_rate(), 0x0000_000E);
+        assert_eq!(header.entry_point(), 0x8010_0400);
+        assert_eq!(header.release(), 0x0000_144B);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_json_round_trip() {
+        let mut header = Header::template(0x8024_6000, *b"SUPER MARIO 64      ", *b"NSME").with_crcs(0x635A_2BFF, 0x8B02_2326);
+        header.set_release(0x1444);
+        let json = serde_json::to_value(header).unwrap();
+        assert_eq!(json["name"], "SUPER MARIO 64");
+        assert_eq!(json["magic"], Magic::CANONICAL);
+        assert_eq!(json["crc1"], "0x635A2BFF");
+        assert_eq!(json["crc2"], "0x8B022326");
+        assert_eq!(json["media"], "NSME");
+        assert!(json.get("_reserved_1").is_none());
+
+        let parsed: Header = serde_json::from_value(json).unwrap();
+        assert!(parsed == header);
+    }
+
+    #[test]
+    fn media_cartridge_type() {
+        let media = Media::new(b'N', b"SM", b'E');
+        assert_eq!(media.as_str().unwrap(), "NSME");
+        assert_eq!(media.cartridge_type(), Some(CartridgeType::Cartridge));
+        assert_eq!(Media::new(b'D', b"MJ", b'J').cartridge_type(), Some(CartridgeType::Disk));
+        assert_eq!(Media::new(b'X', b"SM", b'E').cartridge_type(), None);
+        assert_eq!(CartridgeType::ExpandableCartridge.to_string(), "Expandable Cartridge");
+    }
+
+    #[test]
+    fn magic_try_from() {
+        assert!(matches!(Magic::try_from(&[0x80, 0x37, 0x12]), Err(Error::InsufficientBytes(3))));
+        assert!(matches!(Magic::infer_byte_order(&[0x80, 0x37, 0x12]), Err(Error::InsufficientBytes(3))));
+        let magic = Magic::try_from(&[0x80, 0x37, 0x12, 0x40]).unwrap();
+        assert_eq!(magic.to_u32(), Magic::CANONICAL);
+    }
+
+    #[test]
+    fn magic_kind() {
+        assert_eq!(Magic::infer_kind(&[0x37, 0x80, 0x40, 0x12]).unwrap(), MagicKind::Cartridge(Endianness::Mixed));
+        assert_eq!(Magic::infer_kind(&[0xE8, 0x48, 0xD3, 0x16]).unwrap(), MagicKind::Disk);
+        assert_eq!(Magic::from(&[0x22, 0x63, 0xEE, 0x56]).kind().unwrap(), MagicKind::Disk);
+        assert!(matches!(Magic::infer_kind(&[0x12, 0x34, 0x56, 0x78]), Err(Error::UnknownByteOrder(0x1234_5678))));
+        assert!(matches!(Magic::infer_kind(&[0xE8, 0x48]), Err(Error::InsufficientBytes(2))));
+
+        // Disk images have no byte order to infer
+        assert!(Magic::infer_byte_order(&[0xE8, 0x48, 0xD3, 0x16]).is_err());
+    }
+
+    #[test]
+    fn set_media_round_trip() {
+        let mut header = Header::template(0x8000_0400, *b"SET MEDIA           ", *b"N\0\0E");
+        assert_eq!(header.region(), Region::NorthAmerica);
+        header.set_media(b"N\0\0J");
+        let mut output = Vec::new();
+        header.write(&mut output).unwrap();
+        assert_eq!(&output[0x3B..0x3F], b"N\0\0J");
+        let reread = Header::read(&mut &output[..]).unwrap();
+        assert_eq!(reread.region(), Region::Japan);
+        assert!(reread.media() == header.media());
+
+        assert!(matches!(header.try_set_media(b"NSME"), Ok(())));
+        assert!(matches!(header.try_set_media(b"NSM"), Err(Error::MediaLength(3))));
+        assert_eq!(header.media().as_str().unwrap(), "NSME");
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let header = Header::template(0x8000_0400, *b"FROM SLICE          ", *b"N\0\0E");
+        let mut data = Vec::new();
+        header.write(&mut data).unwrap();
+
+        assert!(Header::try_from(&data[..]).unwrap() == header);
+        data.extend_from_slice(&[0xFF; 16]);
+        assert!(Header::try_from(&data[..]).unwrap() == header);
+        let result = Header::try_from(&data[..Header::SIZE - 1]);
+        assert!(matches!(result, Err(Error::HeaderLength(63))));
+    }
+
+    #[test]
+    fn region_from_media() {
+        let mut header = Header::default();
+        header.media.as_mut().copy_from_slice(b"NSMJ");
+        assert_eq!(header.region_code(), 'J');
+        assert_eq!(header.region(), Region::Japan);
+        header.media.as_mut().copy_from_slice(b"NSMQ");
+        assert_eq!(header.region(), Region::Unknown('Q'));
+    }
 }
@@ -1,6 +1,5 @@
 use byteorder::{BigEndian, ByteOrder};
 use crc32fast::Hasher;
-use itertools::Itertools;
 use std::fmt;
 use std::fs::File;
 use std::io::{self, Read, Write};
@@ -11,38 +10,212 @@ use thiserror::Error;
 pub const IPL_SIZE: usize = 0x0fc0;
 pub const PROGRAM_SIZE: usize = 1024 * 1024;
 
+/// Offset of the 64-word table hidden in the 6105 IPL3, whose words are used to compute CRC values.
+const CHECKSUM_TABLE_OFFSET: usize = 0x710;
+/// Amount of words in the 6105 checksum table.
+const CHECKSUM_TABLE_WORDS: usize = 64;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("{0}")]
     IOError(#[from] io::Error),
     #[error("Unable to read IPL3: Expected file size {}, found {0}", IPL_SIZE)]
     FileSizeError(u64),
+    /// The IPL3 bootcode was not recognized, carrying its CRC32.
+    #[error("Unrecognized IPL3 bootcode with CRC32 {0:#010X}")]
+    UnknownBootcode(u32),
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+/// CIC variants, without the IPL3 bootcode.
+pub enum Cic {
+    Cic6101,
+    Cic6102,
+    Cic6103,
+    Cic6105,
+    Cic6106,
+    Cic7102,
+    Unknown,
+}
+
+impl fmt::Display for Cic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Formula used to combine the accumulators into the final CRC values.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+crate enum CrcFormula {
+    /// `a ^ b ^ c`
+    Xor,
+    /// `(a ^ b) + c`
+    Add,
+    /// `a * b + c`
+    Mul,
+}
+
+/// Per-variant CIC details, which all CIC-specific behavior is derived from.
+#[derive(Debug)]
+crate struct CicInfo {
+    crate cic: Cic,
+    crate name: &'static str,
+    crate short_name: &'static str,
+    /// CRC32 of the IPL3 bootcode used to recognize this variant.
+    crate crc32: Option<u32>,
+    /// Initial value of the checksum accumulators.
+    crate seed: u32,
+    /// Amount which the IPL3 adds to the header entry point.
+    crate offset: u32,
+    crate formula: CrcFormula,
+    /// Whether the checksum mixes in the 64-word table hidden in the IPL3 bootcode.
+    crate ipl_table: bool,
+}
+
+/// Table of CIC variants. The `Cic::Unknown` entry is last, and is assumed to behave like 6102.
+///
+/// Note that 6101, 6102 and 7102 genuinely share the same seed and formula, and so compute identical
+/// CRC values. They differ only in their bootcode.
+crate static CIC_TABLE: [CicInfo; 7] = [
+    CicInfo {
+        cic: Cic::Cic6101,
+        name: "CIC-NUS-6101",
+        short_name: "6101",
+        crc32: Some(0x6170_a4a1),
+        seed: 0xf8ca_4ddc,
+        offset: 0,
+        formula: CrcFormula::Xor,
+        ipl_table: false,
+    },
+    CicInfo {
+        cic: Cic::Cic6102,
+        name: "CIC-NUS-6102",
+        short_name: "6102",
+        crc32: Some(0x90bb_6cb5),
+        seed: 0xf8ca_4ddc,
+        offset: 0,
+        formula: CrcFormula::Xor,
+        ipl_table: false,
+    },
+    CicInfo {
+        cic: Cic::Cic6103,
+        name: "CIC-NUS-6103",
+        short_name: "6103",
+        crc32: Some(0x0b05_0ee0),
+        seed: 0xa388_6759,
+        offset: 0x0010_0000,
+        formula: CrcFormula::Add,
+        ipl_table: false,
+    },
+    CicInfo {
+        cic: Cic::Cic6105,
+        name: "CIC-NUS-6105",
+        short_name: "6105",
+        crc32: Some(0x98bc_2c86),
+        seed: 0xdf26_f436,
+        offset: 0,
+        formula: CrcFormula::Xor,
+        ipl_table: true,
+    },
+    CicInfo {
+        cic: Cic::Cic6106,
+        name: "CIC-NUS-6106",
+        short_name: "6106",
+        crc32: Some(0xacc8_580a),
+        seed: 0x1fea_617a,
+        offset: 0x0020_0000,
+        formula: CrcFormula::Mul,
+        ipl_table: false,
+    },
+    CicInfo {
+        cic: Cic::Cic7102,
+        name: "CIC-NUS-7102",
+        short_name: "7102",
+        crc32: Some(0x009e_9ea3),
+        seed: 0xf8ca_4ddc,
+        offset: 0,
+        formula: CrcFormula::Xor,
+        ipl_table: false,
+    },
+    CicInfo {
+        cic: Cic::Unknown,
+        name: "Unknown",
+        short_name: "unknown",
+        crc32: None,
+        seed: 0xf8ca_4ddc,
+        offset: 0,
+        formula: CrcFormula::Xor,
+        ipl_table: false,
+    },
+];
+
+impl Cic {
+    /// Get all recognized CIC variants, not including `Cic::Unknown`.
+    pub fn all() -> &'static [Cic] {
+        &[
+            Self::Cic6101,
+            Self::Cic6102,
+            Self::Cic6103,
+            Self::Cic6105,
+            Self::Cic6106,
+            Self::Cic7102,
+        ]
+    }
+
+    /// Get the details of this variant from the CIC table.
+    crate fn info(&self) -> &'static CicInfo {
+        CIC_TABLE.iter().find(|info| info.cic == *self).unwrap()
+    }
+
+    /// Get the CRC32 of the IPL3 bootcode used to recognize this variant.
+    pub fn crc32(&self) -> Option<u32> {
+        self.info().crc32
+    }
+
+    /// Get the full CIC name, such as "CIC-NUS-6102".
+    pub fn name(&self) -> &'static str {
+        self.info().name
+    }
+
+    /// Get the short CIC code without the "CIC-NUS-" prefix, such as "6102".
+    pub fn short_name(&self) -> &'static str {
+        self.info().short_name
+    }
+
+    /// Get the CIC variant from its short CIC code, the inverse of `short_name`.
+    ///
+    /// Returns `None` for unrecognized codes, including "unknown".
+    pub fn from_short_name(short_name: &str) -> Option<Cic> {
+        CIC_TABLE
+            .iter()
+            .find(|info| info.cic != Cic::Unknown && info.short_name == short_name)
+            .map(|info| info.cic)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+/// IPL3 bootcode with its CIC variant.
+///
+/// The CRC32 of the bootcode is computed once on construction, so `crc32` is cheap when scanning
+/// many roms.
+pub struct IPL3 {
+    cic: Cic,
+    ipl: [u8; IPL_SIZE],
+    crc32: u32,
 }
 
-#[derive(Clone, Copy)]
-/// IPL3 definitions.
-pub enum IPL3 {
-    Cic6101([u8; IPL_SIZE]),
-    Cic6102([u8; IPL_SIZE]),
-    Cic6103([u8; IPL_SIZE]),
-    Cic6105([u8; IPL_SIZE]),
-    Cic6106([u8; IPL_SIZE]),
-    Cic7102([u8; IPL_SIZE]),
-    Unknown([u8; IPL_SIZE]),
+assert_impl_all!(IPL3: Send, Sync);
+
+/// Compute the CRC32 of IPL3 bootcode.
+fn hash(ipl: &[u8; IPL_SIZE]) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.update(ipl);
+    hasher.finalize()
 }
 
 impl fmt::Display for IPL3 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let s = match self {
-            Self::Cic6101(_) => "CIC-NUS-6101",
-            Self::Cic6102(_) => "CIC-NUS-6102",
-            Self::Cic6103(_) => "CIC-NUS-6103",
-            Self::Cic6105(_) => "CIC-NUS-6105",
-            Self::Cic6106(_) => "CIC-NUS-6106",
-            Self::Cic7102(_) => "CIC-NUS-7102",
-            Self::Unknown(_) => "Unknown",
-        };
-        write!(f, "{}", s)
+        write!(f, "{}", self.cic())
     }
 }
 
@@ -59,19 +232,40 @@ impl IPL3 {
         reader.read_exact(&mut ipl)?;
 
         // Check for known IPLs
-        let mut hasher = Hasher::new();
-        hasher.update(&ipl);
-        let ipl3 = match hasher.finalize() {
-            0x6170_a4a1 => Self::Cic6101(ipl),
-            0x90bb_6cb5 => Self::Cic6102(ipl),
-            0x0b05_0ee0 => Self::Cic6103(ipl),
-            0x98bc_2c86 => Self::Cic6105(ipl),
-            0xacc8_580a => Self::Cic6106(ipl),
-            0x009e_9ea3 => Self::Cic7102(ipl),
-            _ => Self::Unknown(ipl),
-        };
+        let crc32 = hash(&ipl);
+        let cic = CIC_TABLE
+            .iter()
+            .find(|info| info.crc32 == Some(crc32))
+            .map_or(Cic::Unknown, |info| info.cic);
 
-        Ok(ipl3)
+        Ok(Self { cic, ipl, crc32 })
+    }
+
+    /// Read like `read`, but return an error if the bootcode is not recognized.
+    ///
+    /// This lets callers fail instead of computing CRC values for an `Unknown` IPL3, which uses
+    /// default values that are likely incorrect.
+    pub fn read_strict<T: Read>(reader: &mut T) -> Result<Self, Error> {
+        let ipl3 = Self::read(reader)?;
+        match ipl3.cic() {
+            Cic::Unknown => Err(Error::UnknownBootcode(ipl3.crc32())),
+            _ => Ok(ipl3),
+        }
+    }
+
+    /// Get the CRC32 of the IPL3 bootcode.
+    pub fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
+    /// Get the CIC variant.
+    pub fn cic(&self) -> Cic {
+        self.cic
+    }
+
+    /// Construct from a CIC variant and IPL3 bootcode, which is not checked against the variant.
+    pub fn from_cic(cic: Cic, ipl: [u8; IPL_SIZE]) -> Self {
+        Self { cic, ipl, crc32: hash(&ipl) }
     }
 
     pub fn read_path(path: impl AsRef<Path>) -> Result<Self, Error> {
@@ -90,40 +284,39 @@ impl IPL3 {
         Ok(ipl3)
     }
 
+    /// Write the IPL3 bootcode to a file at the given path, which is created or truncated.
+    ///
+    /// The file can be read back with `read_path`.
+    pub fn write_path(&self, path: impl AsRef<Path>) -> Result<usize, Error> {
+        let mut f = File::create(path)?;
+        f.write_all(self.get_ipl())?;
+        Ok(IPL_SIZE)
+    }
+
     pub fn get_ipl(&self) -> &[u8; IPL_SIZE] {
-        match self {
-            Self::Cic6101(bin) => bin,
-            Self::Cic6102(bin) => bin,
-            Self::Cic6103(bin) => bin,
-            Self::Cic6105(bin) => bin,
-            Self::Cic6106(bin) => bin,
-            Self::Cic7102(bin) => bin,
-            Self::Unknown(bin) => bin,
-        }
+        &self.ipl
     }
 
     pub fn compute_crcs(&self, program: &[u8], fs: &[u8]) -> (u32, u32) {
-        let padding_length = (2 - (program.len() & 1)) & 1;
-        let padding = [0; 1];
-        let program = program
-            .iter()
-            .chain(&padding[0..padding_length])
-            .chain(fs.iter())
-            .chain(std::iter::repeat(&0))
-            .take(PROGRAM_SIZE)
-            .cloned()
-            .chunks(4);
+        // Gather the checksummed data into a single buffer: the program padded to an even length,
+        // followed by the fs, and zeros up to the program size
+        let mut data = Vec::with_capacity(PROGRAM_SIZE);
+        data.extend_from_slice(&program[..std::cmp::min(program.len(), PROGRAM_SIZE)]);
+        if data.len() < PROGRAM_SIZE {
+            if program.len() & 1 == 1 {
+                data.push(0);
+            }
+            let amount = std::cmp::min(fs.len(), PROGRAM_SIZE - data.len());
+            data.extend_from_slice(&fs[..amount]);
+        }
+        data.resize(PROGRAM_SIZE, 0);
 
         // Initial checksum value
-        let checksum = match self {
-            Self::Cic6103(_) => 0xa388_6759,
-            Self::Cic6105(_) => 0xdf26_f436,
-            Self::Cic6106(_) => 0x1fea_617a,
-            _ => 0xf8ca_4ddc,
-        };
+        let info = self.cic().info();
+        let checksum = info.seed;
 
         // NUS-IPL3-6105 has a special 64-word table hidden in the IPL
-        let mut ipl = self.get_ipl().chunks(4).skip(452).take(64).cycle();
+        let mut ipl = self.table_words().cycle();
 
         // Six accumulators
         let mut acc1 = Wrapping(checksum);
@@ -138,9 +331,9 @@ impl IPL3 {
         let mut rotated;
 
         // Iterate 1-word at a time
-        for chunk in &program {
+        for chunk in data.chunks_exact(4) {
             // Fetch the current word and rotate it by itself
-            current = Wrapping(BigEndian::read_u32(&chunk.collect::<Vec<_>>()));
+            current = Wrapping(BigEndian::read_u32(chunk));
             rotated = current.rotate_left((current & Wrapping(0x1f)).0);
 
             // Advance accumulator 1
@@ -165,35 +358,62 @@ impl IPL3 {
             }
 
             // Advance accumulator 6
-            match self {
-                Self::Cic6105(_) => {
-                    let current_ipl = ipl.next().unwrap();
-                    let current_ipl = Wrapping(BigEndian::read_u32(&current_ipl));
-                    acc6 += current ^ current_ipl;
-                }
-                _ => {
-                    acc6 += current ^ acc4;
-                }
+            if info.ipl_table {
+                let current_ipl = Wrapping(ipl.next().unwrap());
+                acc6 += current ^ current_ipl;
+            } else {
+                acc6 += current ^ acc4;
             }
         }
 
-        let (crc1, crc2) = match self {
-            Self::Cic6103(_) => ((acc1 ^ acc2) + acc3, (acc4 ^ acc5) + acc6),
-            Self::Cic6106(_) => (acc1 * acc2 + acc3, acc4 * acc5 + acc6),
-            _ => (acc1 ^ acc2 ^ acc3, acc4 ^ acc5 ^ acc6),
+        let (crc1, crc2) = match info.formula {
+            CrcFormula::Add => ((acc1 ^ acc2) + acc3, (acc4 ^ acc5) + acc6),
+            CrcFormula::Mul => (acc1 * acc2 + acc3, acc4 * acc5 + acc6),
+            CrcFormula::Xor => (acc1 ^ acc2 ^ acc3, acc4 ^ acc5 ^ acc6),
         };
 
         (crc1.0, crc2.0)
     }
 
+    /// Compute CRC values by reading program data from a reader.
+    ///
+    /// Only the first `PROGRAM_SIZE` bytes are read, and missing data is treated as zeros, which
+    /// is equivalent to `compute_crcs` with an empty `fs`.
+    pub fn compute_crcs_reader<T: Read>(&self, reader: &mut T) -> io::Result<(u32, u32)> {
+        let mut program = Vec::with_capacity(PROGRAM_SIZE);
+        reader.take(PROGRAM_SIZE as u64).read_to_end(&mut program)?;
+        Ok(self.compute_crcs(&program, &[]))
+    }
+
+    /// Get the words of the checksum table hidden in the bootcode, which only the 6105 uses to
+    /// compute CRC values. Returns `None` for other variants.
+    pub fn checksum_table(&self) -> Option<impl Iterator<Item = u32> + '_> {
+        if self.cic().info().ipl_table {
+            Some(self.table_words())
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the words at the offset of the checksum table, regardless of the variant.
+    fn table_words(&self) -> impl Iterator<Item = u32> + Clone + '_ {
+        let table = &self.get_ipl()[CHECKSUM_TABLE_OFFSET..CHECKSUM_TABLE_OFFSET + (CHECKSUM_TABLE_WORDS * 4)];
+        table.chunks_exact(4).map(BigEndian::read_u32)
+    }
+
     /// Offset the entry point for the current IPL3
     pub fn offset(&self, entry_point: u32) -> u32 {
-        entry_point
-            + match self {
-                Self::Cic6103(_) => 0x0010_0000,
-                Self::Cic6106(_) => 0x0020_0000,
-                _ => 0,
-            }
+        entry_point + self.entry_delta()
+    }
+
+    /// Get the amount which `offset` adds to the entry point for the current IPL3.
+    pub fn entry_delta(&self) -> u32 {
+        self.cic().info().offset
+    }
+
+    /// Get the short CIC code without the "CIC-NUS-" prefix, such as "6102".
+    pub fn short_name(&self) -> &'static str {
+        self.cic().short_name()
     }
 
     pub fn write<T: Write>(&self, writer: &'_ mut T) -> io::Result<usize> {
@@ -208,7 +428,7 @@ mod tests {
 
     #[test]
     fn crc_ipl3_6101() {
-        let ipl3 = IPL3::Cic6101([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6101, [0; IPL_SIZE]);
         let program: Vec<u8> = (0..PROGRAM_SIZE).map(|i| i as u8).collect();
 
         let (crc1, crc2) = ipl3.compute_crcs(&program, &[]);
@@ -219,7 +439,7 @@ mod tests {
 
     #[test]
     fn crc_ipl3_6102() {
-        let ipl3 = IPL3::Cic6102([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]);
         let program: Vec<u8> = (0..PROGRAM_SIZE).map(|i| i as u8).collect();
 
         let (crc1, crc2) = ipl3.compute_crcs(&program, &[]);
@@ -230,7 +450,7 @@ mod tests {
 
     #[test]
     fn crc_ipl3_6103() {
-        let ipl3 = IPL3::Cic6103([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6103, [0; IPL_SIZE]);
         let program: Vec<u8> = (0..PROGRAM_SIZE).map(|i| i as u8).collect();
 
         let (crc1, crc2) = ipl3.compute_crcs(&program, &[]);
@@ -241,7 +461,7 @@ mod tests {
 
     #[test]
     fn crc_ipl3_6105() {
-        let ipl3 = IPL3::Cic6105([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6105, [0; IPL_SIZE]);
         let program: Vec<u8> = (0..PROGRAM_SIZE).map(|i| i as u8).collect();
 
         let (crc1, crc2) = ipl3.compute_crcs(&program, &[]);
@@ -252,7 +472,7 @@ mod tests {
 
     #[test]
     fn crc_ipl3_6106() {
-        let ipl3 = IPL3::Cic6106([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6106, [0; IPL_SIZE]);
         let program: Vec<u8> = (0..PROGRAM_SIZE).map(|i| i as u8).collect();
 
         let (crc1, crc2) = ipl3.compute_crcs(&program, &[]);
@@ -263,7 +483,7 @@ mod tests {
 
     #[test]
     fn crc_ipl3_7102() {
-        let ipl3 = IPL3::Cic7102([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic7102, [0; IPL_SIZE]);
         let program: Vec<u8> = (0..PROGRAM_SIZE).map(|i| i as u8).collect();
 
         let (crc1, crc2) = ipl3.compute_crcs(&program, &[]);
@@ -272,39 +492,194 @@ mod tests {
         assert_eq!(crc2, 0xb2de_a121);
     }
 
+    #[test]
+    fn crc_reader_ipl3() {
+        let program: Vec<u8> = (0..PROGRAM_SIZE + 0x100).map(|i| i as u8).collect();
+        for &cic in Cic::all() {
+            let ipl3 = IPL3::from_cic(cic, [0; IPL_SIZE]);
+            let expected = ipl3.compute_crcs(&program, &[]);
+            assert_eq!(ipl3.compute_crcs_reader(&mut &program[..]).unwrap(), expected);
+            // Short program data is padded with zeros.
+            let expected = ipl3.compute_crcs(&program[..0x1001], &[]);
+            assert_eq!(ipl3.compute_crcs_reader(&mut &program[..0x1001]).unwrap(), expected);
+        }
+    }
+
     #[test]
     fn offset_ipl3_6101() {
-        let ipl3 = IPL3::Cic6101([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6101, [0; IPL_SIZE]);
         assert_eq!(ipl3.offset(0x8000_0400), 0x8000_0400);
     }
 
     #[test]
     fn offset_ipl3_6102() {
-        let ipl3 = IPL3::Cic6102([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]);
         assert_eq!(ipl3.offset(0x8000_0400), 0x8000_0400);
     }
 
     #[test]
     fn offset_ipl3_6103() {
-        let ipl3 = IPL3::Cic6103([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6103, [0; IPL_SIZE]);
         assert_eq!(ipl3.offset(0x8000_0400), 0x8010_0400);
     }
 
     #[test]
     fn offset_ipl3_6105() {
-        let ipl3 = IPL3::Cic6105([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6105, [0; IPL_SIZE]);
         assert_eq!(ipl3.offset(0x8000_0400), 0x8000_0400);
     }
 
     #[test]
     fn offset_ipl3_6106() {
-        let ipl3 = IPL3::Cic6106([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic6106, [0; IPL_SIZE]);
         assert_eq!(ipl3.offset(0x8000_0400), 0x8020_0400);
     }
 
     #[test]
     fn offset_ipl3_7102() {
-        let ipl3 = IPL3::Cic7102([0; IPL_SIZE]);
+        let ipl3 = IPL3::from_cic(Cic::Cic7102, [0; IPL_SIZE]);
         assert_eq!(ipl3.offset(0x8000_0400), 0x8000_0400);
     }
+
+    #[test]
+    fn entry_delta_ipl3_6101() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6101, [0; IPL_SIZE]);
+        assert_eq!(ipl3.entry_delta(), 0);
+    }
+
+    #[test]
+    fn entry_delta_ipl3_6102() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]);
+        assert_eq!(ipl3.entry_delta(), 0);
+    }
+
+    #[test]
+    fn entry_delta_ipl3_6103() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6103, [0; IPL_SIZE]);
+        assert_eq!(ipl3.entry_delta(), 0x0010_0000);
+    }
+
+    #[test]
+    fn entry_delta_ipl3_6105() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6105, [0; IPL_SIZE]);
+        assert_eq!(ipl3.entry_delta(), 0);
+    }
+
+    #[test]
+    fn entry_delta_ipl3_6106() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6106, [0; IPL_SIZE]);
+        assert_eq!(ipl3.entry_delta(), 0x0020_0000);
+    }
+
+    #[test]
+    fn entry_delta_ipl3_7102() {
+        let ipl3 = IPL3::from_cic(Cic::Cic7102, [0; IPL_SIZE]);
+        assert_eq!(ipl3.entry_delta(), 0);
+    }
+
+    #[test]
+    fn short_name_ipl3() {
+        assert_eq!(IPL3::from_cic(Cic::Cic6101, [0; IPL_SIZE]).short_name(), "6101");
+        assert_eq!(IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]).short_name(), "6102");
+        assert_eq!(IPL3::from_cic(Cic::Cic6103, [0; IPL_SIZE]).short_name(), "6103");
+        assert_eq!(IPL3::from_cic(Cic::Cic6105, [0; IPL_SIZE]).short_name(), "6105");
+        assert_eq!(IPL3::from_cic(Cic::Cic6106, [0; IPL_SIZE]).short_name(), "6106");
+        assert_eq!(IPL3::from_cic(Cic::Cic7102, [0; IPL_SIZE]).short_name(), "7102");
+        assert_eq!(IPL3::from_cic(Cic::Unknown, [0; IPL_SIZE]).short_name(), "unknown");
+    }
+
+    #[test]
+    fn cic_all_known() {
+        for &cic in Cic::all() {
+            assert!(cic.crc32().is_some());
+            assert_eq!(IPL3::from_cic(cic, [0; IPL_SIZE]).cic(), cic);
+        }
+        assert!(!Cic::all().contains(&Cic::Unknown));
+        assert_eq!(Cic::Unknown.crc32(), None);
+    }
+
+    #[test]
+    fn cic_table_has_each_variant_once() {
+        assert_eq!(CIC_TABLE.len(), Cic::all().len() + 1);
+        for &cic in Cic::all().iter().chain(&[Cic::Unknown]) {
+            assert_eq!(CIC_TABLE.iter().filter(|info| info.cic == cic).count(), 1);
+        }
+        assert_eq!(CIC_TABLE.last().unwrap().cic, Cic::Unknown);
+    }
+
+    #[test]
+    fn crc32_ipl3() {
+        let ipl = [0x5A; IPL_SIZE];
+        let mut hasher = Hasher::new();
+        hasher.update(&ipl);
+        let crc32 = hasher.finalize();
+        assert_eq!(IPL3::from_cic(Cic::Unknown, ipl).crc32(), crc32);
+        // The CRC32 describes the given bootcode, not the variant
+        assert_eq!(IPL3::from_cic(Cic::Cic6105, ipl).crc32(), crc32);
+        assert_eq!(IPL3::read(&mut &ipl[..]).unwrap().crc32(), crc32);
+    }
+
+    #[test]
+    fn cic_6101_6102_7102_share_crc_handling() {
+        let (a, b, c) = (Cic::Cic6101.info(), Cic::Cic6102.info(), Cic::Cic7102.info());
+        assert_eq!((a.seed, a.formula, a.ipl_table), (b.seed, b.formula, b.ipl_table));
+        assert_eq!((c.seed, c.formula, c.ipl_table), (b.seed, b.formula, b.ipl_table));
+        assert_eq!(b.seed, 0xf8ca_4ddc);
+    }
+
+    #[test]
+    fn cic_from_short_name() {
+        for &cic in Cic::all() {
+            assert_eq!(Cic::from_short_name(cic.short_name()), Some(cic));
+        }
+        assert_eq!(Cic::from_short_name("6102"), Some(Cic::Cic6102));
+        assert_eq!(Cic::from_short_name("unknown"), None);
+        assert_eq!(Cic::from_short_name("CIC-NUS-6102"), None);
+    }
+
+    #[test]
+    fn read_strict_unknown_bootcode() {
+        let ipl = [0x5A; IPL_SIZE];
+        let crc32 = IPL3::from_cic(Cic::Unknown, ipl).crc32();
+        match IPL3::read_strict(&mut &ipl[..]) {
+            Err(Error::UnknownBootcode(value)) => assert_eq!(value, crc32),
+            _ => panic!("Expected UnknownBootcode error"),
+        }
+        assert_eq!(IPL3::read(&mut &ipl[..]).unwrap().cic(), Cic::Unknown);
+    }
+
+    #[test]
+    fn checksum_table_6105() {
+        let mut ipl = [0; IPL_SIZE];
+        for (i, byte) in ipl.iter_mut().enumerate() {
+            *byte = (i / 4) as u8;
+        }
+        let table: Vec<u32> = IPL3::from_cic(Cic::Cic6105, ipl).checksum_table().unwrap().collect();
+        assert_eq!(table.len(), 64);
+        assert_eq!(table[0], 0xC4C4_C4C4);
+        assert_eq!(table[63], 0x0303_0303);
+        assert!(IPL3::from_cic(Cic::Cic6102, ipl).checksum_table().is_none());
+    }
+
+    #[test]
+    fn write_path_round_trip() {
+        let mut ipl = [0; IPL_SIZE];
+        for (i, byte) in ipl.iter_mut().enumerate() {
+            *byte = (i * 7) as u8;
+        }
+        let ipl3 = IPL3::from_cic(Cic::Unknown, ipl);
+        let path = std::env::temp_dir().join(format!("n64rom-ipl3-{}.bin", std::process::id()));
+        assert_eq!(ipl3.write_path(&path).unwrap(), IPL_SIZE);
+        assert_eq!(IPL3::read_path(&path).unwrap(), ipl3);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn eq_ipl3() {
+        let mut ipl = [0; IPL_SIZE];
+        ipl[0x100] = 0x5A;
+        assert_eq!(IPL3::from_cic(Cic::Cic6102, ipl), IPL3::from_cic(Cic::Cic6102, ipl));
+        assert_ne!(IPL3::from_cic(Cic::Cic6102, ipl), IPL3::from_cic(Cic::Cic6101, ipl));
+        assert_ne!(IPL3::from_cic(Cic::Cic6102, ipl), IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]));
+    }
 }
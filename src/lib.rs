@@ -1,5 +1,6 @@
 #![deny(clippy::all)]
 #![allow(clippy::field_reassign_with_default)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 #![feature(box_syntax)]
 #![feature(crate_visibility_modifier)]
 #![feature(try_trait)]
@@ -10,8 +11,11 @@
 extern crate static_assertions;
 
 pub mod convert;
+mod error;
 pub mod header;
 pub mod ipl3;
 pub mod rom;
 pub mod stream;
 pub mod util;
+
+pub use error::Error;
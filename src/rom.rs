@@ -1,9 +1,11 @@
 use std::fmt;
-use std::io::{self, Read, Write};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
 use thiserror::Error;
 
-use crate::header::Header;
-use crate::ipl3::{IPL3, IPL_SIZE};
+use crate::header::{Header, Magic, Region};
+use crate::ipl3::{Cic, IPL3, IPL_SIZE, PROGRAM_SIZE};
 use crate::stream::{Reader, Writer};
 use crate::util::{FileSize, MEBIBYTE};
 
@@ -13,14 +15,25 @@ pub const HEAD_SIZE: usize = Header::SIZE + IPL_SIZE;
 /// Maximum expected rom size (64 MiB).
 pub const MAX_SIZE: usize = 1024 * 1024 * 64;
 
+/// Size of the header which some backup devices ("copiers") prepend to rom files.
+pub const COPIER_HEADER_SIZE: usize = 0x200;
+
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("{0}")]
+    #[error("Unable to read or write rom data: {0}")]
     IOError(#[from] io::Error),
-    #[error("{0}")]
+    /// The image could not be converted to or from big-endian, such as for `from_image`.
+    #[error("Unable to convert rom data to big-endian: {0}")]
+    ConvertError(#[from] crate::convert::Error),
+    /// The header could not be read from the image, or built for `Rom::new`.
+    #[error("Unable to read or build rom header: {0}")]
     HeaderError(#[from] crate::header::Error),
-    #[error("Unsupported endianness for this operation: {0}")]
-    UnsupportedEndianness(Endianness),
+    /// The requested padded size is smaller than the image.
+    #[error("Cannot pad image of {1} bytes to smaller size of {0} bytes; trim the image first or choose a larger size")]
+    PadSizeError(usize, usize),
+    /// The filesystem offset is odd, which would checksum a pad byte that is not in the image.
+    #[error("Filesystem offset {0:#X} must be 2-byte aligned; use set_fs to round it up")]
+    FsOffsetAlignment(usize),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -31,6 +44,19 @@ pub enum Endianness {
     Mixed,
 }
 
+impl Endianness {
+    /// Infer the byte order from the magic number at the current position of a reader, without
+    /// consuming it. The reader is sought back to its original position, even on error.
+    pub fn detect<T: Read + Seek>(reader: &mut T) -> Result<Self, crate::header::Error> {
+        let position = reader.stream_position()?;
+        let mut magic = [0; 4];
+        let result = reader.read_exact(&mut magic);
+        reader.seek(SeekFrom::Start(position))?;
+        result?;
+        Magic::infer_byte_order(&magic)
+    }
+}
+
 impl fmt::Display for Endianness {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -41,6 +67,29 @@ impl fmt::Display for Endianness {
     }
 }
 
+/// Outcome of verifying the header CRC values, see `Rom::verify`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VerifyStatus {
+    /// The stored CRC values match the computed values.
+    Ok,
+    /// The stored CRC values do not match the computed values.
+    CrcMismatch,
+    /// The CIC is not recognized, so the computed CRC values cannot be trusted.
+    UnknownCic,
+}
+
+/// Report of verifying the header CRC values of a `Rom`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VerifyReport {
+    /// CRC values stored in the header.
+    pub stored: (u32, u32),
+    /// CRC values computed from the body.
+    pub computed: (u32, u32),
+    /// CIC variant used to compute the CRC values.
+    pub cic: Cic,
+    pub status: VerifyStatus,
+}
+
 #[derive(Clone)]
 pub struct Rom {
     pub header: Header,
@@ -49,38 +98,183 @@ pub struct Rom {
     pub image: Vec<u8>,
     /// Byte order (endianness) of rom file.
     order: Endianness,
+    /// Offset of filesystem data within the body, if any.
+    fs_offset: Option<usize>,
 }
 
+/// Compares the logical content of two roms: the header, IPL3 and big-endian body.
+///
+/// The byte order is intentionally excluded, so the same rom read from files with different
+/// byte orders compares equal.
+impl PartialEq for Rom {
+    fn eq(&self, other: &Self) -> bool {
+        self.header == other.header
+            && self.ipl3.get_ipl() == other.ipl3.get_ipl()
+            && self.data() == other.data()
+    }
+}
+
+impl Eq for Rom {}
+
+// Assert roms may be shared across threads, such as by batch operations.
+assert_impl_all!(Rom: Send, Sync);
+assert_impl_all!(Endianness: Send, Sync);
+
 impl fmt::Display for Rom {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut builder = Vec::<String>::new();
         builder.push(format!("{}", self.header));
+        builder.push(format!("  Region: {}", self.region()));
         builder.push(format!("  IPL3: {}", self.ipl3));
         builder.push(format!("  Byte Order: {}", self.order));
         // Only show rom size if we have data.
         if self.image.len() > HEAD_SIZE {
-            let filesize = FileSize::from(self.len() as u64, MEBIBYTE);
-            match filesize {
-                FileSize::Float(value) => {
-                    builder.push(format!("  Rom Size: {:.*} MiB", 1, value));
-                }
-                FileSize::Int(value) => {
-                    builder.push(format!("  Rom Size: {} MiB", value));
-                }
-            }
+            builder.push(format!("  Rom Size: {}", FileSize::human(self.len() as u64)));
         }
         write!(f, "{}", builder.join("\n"))
     }
 }
 
+/// Reader over the big-endian body of a `Rom`, yielding data in a chosen byte order.
+struct BodyReader<'a> {
+    data: &'a [u8],
+    order: Endianness,
+    position: usize,
+}
+
+impl<'a> Read for BodyReader<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // A trailing partial word is passed through without conversion
+        let aligned = self.data.len() - (self.data.len() % 4);
+        let amount = std::cmp::min(buf.len(), self.data.len() - self.position);
+        for (idx, byte) in buf[..amount].iter_mut().enumerate() {
+            let pos = self.position + idx;
+            let src = match self.order {
+                Endianness::Little if pos < aligned => pos ^ 3,
+                Endianness::Mixed if pos < aligned => pos ^ 1,
+                _ => pos,
+            };
+            *byte = self.data[src];
+        }
+        self.position += amount;
+        Ok(amount)
+    }
+}
+
+/// Writer which appends to the body of a `Rom`.
+struct BodyWriter<'a> {
+    rom: &'a mut Rom,
+    /// Whether data has been written since the CRC values were last computed.
+    dirty: bool,
+}
+
+impl<'a> BodyWriter<'a> {
+    /// Recompute the header CRC values if any data has been written.
+    fn finalize(&mut self) {
+        if self.dirty {
+            self.rom.correct_crc();
+            self.dirty = false;
+        }
+    }
+}
+
+impl<'a> Write for BodyWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.rom.image.extend_from_slice(buf);
+        self.dirty = true;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.finalize();
+        Ok(())
+    }
+}
+
+impl<'a> Drop for BodyWriter<'a> {
+    fn drop(&mut self) {
+        self.finalize();
+    }
+}
+
 impl Rom {
+    /// Get a reader over the Rom body, not including header or IPL3, in the stored byte order.
+    pub fn body_reader(&self) -> impl Read + '_ {
+        BodyReader {
+            data: self.data(),
+            order: self.order,
+            position: 0,
+        }
+    }
+
+    /// Get a writer which appends big-endian data to the end of the Rom body.
+    ///
+    /// The header CRC values are recomputed when the writer is flushed or dropped.
+    pub fn body_writer(&mut self) -> impl Write + '_ {
+        BodyWriter {
+            rom: self,
+            dirty: false,
+        }
+    }
+
     pub fn check_crc(&self) -> (bool, (u32, u32)) {
         let crcs = self.header.crcs();
-        let calc = self.ipl3.compute_crcs(&self.image[HEAD_SIZE..], &[]);
+        let calc = self.compute_crcs_with(&self.ipl3);
         let result = crcs == calc;
         (result, calc)
     }
 
+    /// Compute the CRC values of the body using the given IPL3, split at the fs offset if any.
+    fn compute_crcs_with(&self, ipl3: &IPL3) -> (u32, u32) {
+        match self.fs_offset {
+            Some(offset) => {
+                // The body may have since been truncated before the fs data
+                let (program, fs) = self.data().split_at(std::cmp::min(offset, self.data().len()));
+                ipl3.compute_crcs(program, fs)
+            }
+            None => ipl3.compute_crcs(self.data(), &[]),
+        }
+    }
+
+    /// Verify the header CRC values, reporting the stored and computed values.
+    ///
+    /// Unlike `check_crc`, a rom with an unrecognized CIC is reported as `VerifyStatus::UnknownCic`,
+    /// whether or not the values match.
+    pub fn verify(&self) -> VerifyReport {
+        let (result, computed) = self.check_crc();
+        let cic = self.cic();
+        let status = match (cic, result) {
+            (Cic::Unknown, _) => VerifyStatus::UnknownCic,
+            (_, true) => VerifyStatus::Ok,
+            (_, false) => VerifyStatus::CrcMismatch,
+        };
+        VerifyReport {
+            stored: self.header.crcs(),
+            computed,
+            cic,
+            status,
+        }
+    }
+
+    /// Get the CIC variant of the IPL3.
+    pub fn cic(&self) -> Cic {
+        self.ipl3.cic()
+    }
+
+    /// Guess the CIC variant by finding one whose computed CRC values match the header.
+    ///
+    /// This can identify the CIC of a rom whose IPL3 is not recognized, but requires computing the
+    /// CRC values once per variant. Since 6101, 6102 and 7102 compute identical CRC values, 6102
+    /// (the most common) is preferred among them. Returns `None` if no variant matches.
+    pub fn guess_cic(&self) -> Option<Cic> {
+        const PREFERRED: [Cic; 6] = [Cic::Cic6102, Cic::Cic6101, Cic::Cic7102, Cic::Cic6103, Cic::Cic6105, Cic::Cic6106];
+        let crcs = self.header.crcs();
+        PREFERRED.iter().copied().find(|&cic| {
+            let ipl3 = IPL3::from_cic(cic, *self.ipl3.get_ipl());
+            self.compute_crcs_with(&ipl3) == crcs
+        })
+    }
+
     /// Correct the CRC values in the header.
     pub fn correct_crc(&mut self) -> bool {
         let (result, (calc1, calc2)) = self.check_crc();
@@ -106,17 +300,51 @@ impl Rom {
         &mut self.image[HEAD_SIZE..]
     }
 
-    /// Construct from a raw image without copying. Requires image data to be in big-endian format.
-    pub fn from_image(image: Vec<u8>) -> Result<Self, Error> {
+    /// Construct from a raw image without copying. Image data in little-endian or mixed format is
+    /// converted to big-endian in place, and the original byte order is kept for `write`.
+    pub fn from_image(mut image: Vec<u8>) -> Result<Self, Error> {
+        // Infer endianness.
+        let order = Magic::infer_byte_order(&image)?;
+        if order != Endianness::Big {
+            crate::convert::convert(&mut image, order, Endianness::Big)?;
+        }
+
+        // Read header & IPL3 from the big-endian image.
         let mut head = &image[..HEAD_SIZE];
-        // Read header & infer endianness.
-        let (header, order) = Header::read_ordered(&mut head)?;
-        if order == Endianness::Big {
-            let ipl3 = IPL3::read(&mut head)?;
-            Ok(Rom::from(header, ipl3, image, order))
+        let header = Header::read(&mut head)?;
+        let ipl3 = IPL3::read(&mut head)?;
+        Ok(Rom::from(header, ipl3, image, order))
+    }
+
+    /// Build a big-endian rom from an IPL3, program data and filesystem data.
+    ///
+    /// The header is built with the given fields and CRC values computed by the IPL3, see
+    /// `Header::new`. The filesystem data follows the program in the body, and its offset is
+    /// recorded as with `set_fs`. As with `compute_crcs`, an odd-length program is padded with a
+    /// zero byte before the filesystem data. The media format must be 4 bytes.
+    pub fn new(ipl3: IPL3, entry_point: u32, name: &str, media: &[u8], program: Vec<u8>, fs: &[u8]) -> Result<Self, Error> {
+        let header = Header::new(entry_point, name, media, &program, fs, &ipl3)?;
+
+        // Assemble the image, reusing the program buffer for the body. The pad byte is stored, so
+        // the CRC values also match the image once the fs offset is no longer known.
+        let mut body = program;
+        let fs_offset = if fs.is_empty() {
+            None
         } else {
-            Err(Error::UnsupportedEndianness(order))
-        }
+            if body.len() % 2 == 1 {
+                body.push(0);
+            }
+            Some(body.len())
+        };
+        let mut image = Vec::with_capacity(HEAD_SIZE + body.len() + fs.len());
+        header.write(&mut image)?;
+        ipl3.write(&mut image)?;
+        image.extend_from_slice(&body);
+        image.extend_from_slice(fs);
+
+        let mut rom = Self::from(header, ipl3, image, Endianness::Big);
+        rom.fs_offset = fs_offset;
+        Ok(rom)
     }
 
     pub fn from(header: Header, ipl3: IPL3, image: Vec<u8>, order: Endianness) -> Self {
@@ -125,9 +353,192 @@ impl Rom {
             ipl3,
             image,
             order,
+            fs_offset: None,
         }
     }
 
+    /// Rewrite the magic number with the canonical big-endian value (`0x80371240`).
+    ///
+    /// This salvages dumps whose first word is corrupt but whose remaining data is intact.
+    pub fn fix_magic(&mut self) {
+        let magic = Magic::new();
+        self.image[..Magic::SIZE].copy_from_slice(magic.as_ref());
+        self.header.magic = magic;
+    }
+
+    /// Get the length of trailing overdump data, which `trim_overdump` would remove.
+    ///
+    /// Overdump data is a trailing run of a single fill byte which extends past the smallest
+    /// power-of-two size able to hold the preceding data. The CRC region is never considered
+    /// overdump data.
+    pub fn overdump_len(&self) -> usize {
+        let fill = match self.image.last() {
+            Some(fill) => *fill,
+            None => return 0,
+        };
+        let run = self.image.iter().rev().take_while(|&&byte| byte == fill).count();
+        let content = std::cmp::max(self.image.len() - run, HEAD_SIZE + PROGRAM_SIZE);
+        self.image.len().saturating_sub(content.next_power_of_two())
+    }
+
+    /// Remove trailing overdump data, returning the amount of bytes removed.
+    ///
+    /// The image is only ever truncated down to a power-of-two size, see `overdump_len`.
+    pub fn trim_overdump(&mut self) -> usize {
+        let overdump = self.overdump_len();
+        self.image.truncate(self.image.len() - overdump);
+        overdump
+    }
+
+    /// Guess whether or not this is a homebrew rom, rather than a commercial release.
+    ///
+    /// This is a heuristic and cannot be certain. It combines the following signals, and requires
+    /// at least two of them:
+    ///
+    /// - The CIC is not recognized.
+    /// - The game code in the media format is empty or a placeholder (NUL bytes or spaces).
+    /// - The image is smaller than the smallest commercial rom (4 MiB).
+    /// - The media format byte is not a known format, see `media_region_mismatch_warning`.
+    pub fn is_homebrew(&self) -> bool {
+        let (_, id1, id2, _) = self.header.media().chars();
+        let placeholder = |c: char| c == '\0' || c == ' ';
+        let signals = [
+            self.cic() == Cic::Unknown,
+            placeholder(id1) && placeholder(id2),
+            self.image.len() < (4 * MEBIBYTE) as usize,
+            self.header.media().cartridge_type().is_none(),
+        ];
+        signals.iter().filter(|&&signal| signal).count() >= 2
+    }
+
+    /// Guess whether or not the rom image is truncated, such as by an interrupted dump.
+    ///
+    /// This is a heuristic and cannot be certain. The image is considered truncated if it does not
+    /// hold the full CRC region, or if its length is not a multiple of 1 MiB (the granularity of
+    /// cartridge sizes) and it does not end in padding (`0x00` or `0xFF` bytes). Homebrew roms of
+    /// arbitrary size may be falsely flagged, and a dump cut off within padding is not detected.
+    pub fn is_truncated(&self) -> bool {
+        if self.image.len() < HEAD_SIZE + PROGRAM_SIZE {
+            return true;
+        }
+        let padded = self.image[self.image.len() - 4..].iter().all(|&byte| byte == 0x00 || byte == 0xFF);
+        (self.image.len() as u64 % MEBIBYTE) != 0 && !padded
+    }
+
+    /// Get the program entry point, without the offset which the IPL3 adds to the header value.
+    pub fn entry_point(&self) -> u32 {
+        self.header.entry_point.wrapping_sub(self.ipl3.entry_delta())
+    }
+
+    /// Set the program entry point, storing it in the header with the offset for the IPL3.
+    ///
+    /// The entry point is not covered by the CRC values, so they do not need to be recomputed.
+    pub fn set_entry_point(&mut self, entry_point: u32) {
+        self.header.entry_point = self.ipl3.offset(entry_point);
+    }
+
+    /// Get the offset of filesystem data within the body, as placed by `set_fs`.
+    pub fn fs_offset(&self) -> Option<usize> {
+        self.fs_offset
+    }
+
+    /// Designate where existing filesystem data begins within the body, or `None` if there is none.
+    ///
+    /// The offset is relative to the start of the body. Data before it is passed to `compute_crcs`
+    /// as the program, and data from it on as the fs, when checking and correcting CRC values.
+    ///
+    /// The offset must be 2-byte aligned, otherwise `Error::FsOffsetAlignment` is returned, as
+    /// `compute_crcs` would pad the program with a byte which is not in the image. For an aligned
+    /// offset, the split computes the same CRC values as the contiguous body.
+    pub fn set_fs_offset(&mut self, offset: Option<usize>) -> Result<(), Error> {
+        match offset {
+            Some(offset) if offset % 2 == 1 => Err(Error::FsOffsetAlignment(offset)),
+            offset => {
+                self.fs_offset = offset;
+                Ok(())
+            }
+        }
+    }
+
+    /// Place filesystem data in the body at the given offset, replacing any data from there on.
+    ///
+    /// The body is laid out as the program data, followed by the filesystem data starting at
+    /// `at_offset` (relative to the start of the body). If the body is shorter than `at_offset` it
+    /// is padded with zeros. An odd `at_offset` is rounded up, storing the zero byte which
+    /// `compute_crcs` pads the program with, so the CRC values cover only data in the image. The
+    /// offset is recorded as with `set_fs_offset`, and returned.
+    ///
+    /// The header CRC values are not updated, see `correct_crc`.
+    pub fn set_fs(&mut self, fs: Vec<u8>, at_offset: usize) -> usize {
+        let offset = at_offset + (at_offset % 2);
+        self.image.resize(HEAD_SIZE + offset, 0);
+        self.image.extend(fs);
+        self.fs_offset = Some(offset);
+        offset
+    }
+
+    /// Get the MD5 digest of the full image, as used by No-Intro DAT files.
+    ///
+    /// The image is hashed in big-endian byte order, regardless of the byte order of the rom file.
+    #[cfg(feature = "hashes")]
+    pub fn md5(&self) -> [u8; 16] {
+        md5::compute(self.full()).0
+    }
+
+    /// Get the MD5 digest of the body, not including header or IPL3.
+    #[cfg(feature = "hashes")]
+    pub fn body_md5(&self) -> [u8; 16] {
+        md5::compute(self.data()).0
+    }
+
+    /// Get the SHA1 digest of the full image, as used by No-Intro DAT files.
+    ///
+    /// The image is hashed in big-endian byte order, regardless of the byte order of the rom file.
+    #[cfg(feature = "hashes")]
+    pub fn sha1(&self) -> [u8; 20] {
+        use sha1::Digest;
+        sha1::Sha1::digest(self.full()).into()
+    }
+
+    /// Get the SHA1 digest of the body, not including header or IPL3.
+    #[cfg(feature = "hashes")]
+    pub fn body_sha1(&self) -> [u8; 20] {
+        use sha1::Digest;
+        sha1::Sha1::digest(self.data()).into()
+    }
+
+    /// Pad the image with the given fill byte up to the given size, returning the amount of bytes
+    /// added.
+    ///
+    /// The padding is appended to the body, leaving the header and IPL3 intact. Errors if the image
+    /// is already larger than `size`. If the image is shorter than the CRC region, padding changes
+    /// the computed CRC values.
+    pub fn pad_to(&mut self, size: usize, fill: u8) -> Result<usize, Error> {
+        let length = self.image.len();
+        if size < length {
+            return Err(Error::PadSizeError(size, length));
+        }
+        self.image.resize(size, fill);
+        Ok(size - length)
+    }
+
+    /// Pad the image with the given fill byte up to the next power-of-two size, returning the
+    /// amount of bytes added.
+    ///
+    /// If the image is shorter than the CRC region, padding changes the computed CRC values.
+    pub fn pad_to_power_of_two(&mut self, fill: u8) -> usize {
+        let size = self.image.len().next_power_of_two();
+        // The next power of two is never smaller than the image.
+        self.pad_to(size, fill).unwrap()
+    }
+
+    /// Get slice of the IPL3 bootcode as it sits in the image, in big-endian order.
+    ///
+    /// This matches `self.ipl3.get_ipl()` unless either was since edited without the other.
+    pub fn ipl3_bytes(&self) -> &[u8] {
+        &self.image[Header::SIZE..HEAD_SIZE]
+    }
+
     /// Get slice of full Rom image data.
     pub fn full(&self) -> &[u8] {
         &self.image[..]
@@ -138,10 +549,126 @@ impl Rom {
         &mut self.image[..]
     }
 
+    /// Get the header, IPL3 and the first `boot_len` bytes of the body as a single big-endian blob.
+    ///
+    /// The header and IPL3 are serialized from `self.header` and `self.ipl3`, so any edits not yet
+    /// written to the image are included. If the body is shorter than `boot_len`, the entire body
+    /// is included.
+    pub fn head_and_boot(&self, boot_len: usize) -> Vec<u8> {
+        let body = self.data();
+        let boot = &body[..std::cmp::min(boot_len, body.len())];
+        let mut blob = Vec::with_capacity(HEAD_SIZE + boot.len());
+        // Writing to a Vec cannot fail.
+        self.header.write(&mut blob).unwrap();
+        self.ipl3.write(&mut blob).unwrap();
+        blob.extend_from_slice(boot);
+        blob
+    }
+
+    /// Get an identifier string for matching the rom against GoodN64-style naming.
+    ///
+    /// The format is `{name} ({country}) [{crc1}-{crc2}]`, where `name` is the internal name with
+    /// surrounding whitespace and NUL bytes trimmed (invalid UTF-8 is replaced), `country` is the
+    /// region code character from the media format, and the CRC values are the header values as
+    /// eight uppercase hexadecimal digits. For example: `SUPER MARIO 64 (E) [635A2BFF-8B022326]`.
+    ///
+    /// The CRC values are taken from the header as-is, see `check_crc` to verify them.
+    pub fn goodn64_id(&self) -> String {
+        let name = String::from_utf8_lossy(self.header.name());
+        let name = name.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+        let (crc1, crc2) = self.header.crcs();
+        format!("{} ({}) [{:08X}-{:08X}]", name, self.region_code(), crc1, crc2)
+    }
+
+    /// Get the destination region decoded from the header media format.
+    pub fn region(&self) -> Region {
+        self.header.region()
+    }
+
+    /// Get the region code character from the header media format.
+    pub fn region_code(&self) -> char {
+        self.header.region_code()
+    }
+
+    /// Recompute all derived fields, making the rom internally consistent after editing.
+    ///
+    /// This touches only the following:
+    ///
+    /// - The IPL3, which is detected again from the bootcode in the image.
+    /// - The header entry point, which is re-offset if the detected CIC changed.
+    /// - The header CRC values, which are recomputed from the current body.
+    ///
+    /// All other header fields such as the name and media format are left intact.
+    pub fn recompute_all(&mut self) {
+        // Detect the IPL3 again, in case the bootcode in the image was modified
+        let previous = self.ipl3;
+        self.ipl3 = IPL3::read(&mut &self.image[Header::SIZE..HEAD_SIZE]).unwrap();
+
+        // Re-offset the entry point for the detected CIC
+        let entry_point = self.header.entry_point.wrapping_sub(previous.entry_delta());
+        self.header.entry_point = self.ipl3.offset(entry_point);
+
+        self.correct_crc();
+    }
+
+    /// Get a warning if the media format or its region code does not follow known conventions.
+    ///
+    /// This is informational only, and can help to spot hacked or corrupt media formats.
+    pub fn media_region_mismatch_warning(&self) -> Option<String> {
+        let (format, _, _, _) = self.header.media().chars();
+        match (self.header.media().cartridge_type().is_some(), self.header.region()) {
+            (false, _) => {
+                let region = self.header.region_code();
+                Some(format!("Unknown media format {:?} with region code {:?}", format, region))
+            }
+            (true, Region::Unknown(region)) => {
+                Some(format!("Unknown region code {:?} for media format {:?}", region, format))
+            }
+            _ => None,
+        }
+    }
+
     pub fn order(&self) -> Endianness {
         self.order
     }
 
+    /// Whether the rom file is in big-endian (`.z64`) byte order.
+    pub fn is_big_endian(&self) -> bool {
+        self.order == Endianness::Big
+    }
+
+    /// Whether the rom file is in byte-swapped little-endian (`.n64`) byte order.
+    pub fn is_byteswapped(&self) -> bool {
+        self.order == Endianness::Little
+    }
+
+    /// Whether the rom file is in mixed (`.v64`) byte order.
+    pub fn is_mixed(&self) -> bool {
+        self.order == Endianness::Mixed
+    }
+
+    /// Whether the rom file must be converted to be written in the given byte order.
+    ///
+    /// ```
+    /// use n64rom::ipl3::{Cic, IPL3, IPL_SIZE};
+    /// use n64rom::rom::{Endianness, Rom};
+    ///
+    /// let ipl3 = IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]);
+    /// let rom = Rom::new(ipl3, 0x8000_0400, "EXAMPLE", b"NEXE", vec![0; 0x10_0000], &[]).unwrap();
+    /// assert!(rom.is_big_endian());
+    /// assert!(!rom.needs_conversion(Endianness::Big));
+    /// assert!(rom.needs_conversion(Endianness::Little));
+    /// ```
+    pub fn needs_conversion(&self, target: Endianness) -> bool {
+        self.order != target
+    }
+
+    /// Read Rom with all data from the file at the given path.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut file = File::open(path)?;
+        Ok(Self::read(&mut file)?)
+    }
+
     /// Read Rom with all data.
     pub fn read<T: Read>(mut reader: &mut T) -> Result<Self, crate::header::Error> {
         Self::read_with_body(&mut reader, true)
@@ -151,7 +678,19 @@ impl Rom {
     pub fn read_with_body<T: Read>(mut reader: &mut T, read_body: bool) -> Result<Self, crate::header::Error> {
         // Read header & infer endianness
         let (header, order) = Header::read_ordered(&mut reader)?;
+        Self::read_remaining(reader, header, order, read_body)
+    }
 
+    /// Read Rom using a known byte order instead of inferring it from the magic number.
+    ///
+    /// This allows reading dumps with a corrupt magic number.
+    pub fn read_with_order<T: Read>(mut reader: &mut T, order: Endianness, read_body: bool) -> Result<Self, crate::header::Error> {
+        let header = Header::read_with_order(&mut reader, order)?;
+        Self::read_remaining(reader, header, order, read_body)
+    }
+
+    /// Read the IPL3 and optionally the body following an already read header.
+    fn read_remaining<T: Read>(mut reader: &mut T, header: Header, order: Endianness, read_body: bool) -> Result<Self, crate::header::Error> {
         // Create new reader based on endianness, read remaining with it
         let mut reader = Reader::from(&mut reader, order);
         let ipl3 = IPL3::read(&mut reader)?;
@@ -166,16 +705,26 @@ impl Rom {
         }
         let image = image;
 
-        let rom = Self {
-            header,
-            ipl3,
-            image,
-            order,
-        };
+        let rom = Self::from(header, ipl3, image, order);
 
         Ok(rom)
     }
 
+    /// Write only the header and IPL3, which identify the rom without including the body.
+    ///
+    /// The byte order defaults to the original order, as with `write`.
+    pub fn write_head<T: Write>(&self, writer: &'_ mut T, endianness: Option<&Endianness>) -> io::Result<usize> {
+        let order = endianness.unwrap_or(&self.order);
+        let mut writer = Writer::from(writer, *order);
+        let written = self.header.write(&mut writer)? + self.ipl3.write(&mut writer)?;
+        writer.flush()?;
+        Ok(written)
+    }
+
+    /// Write the full rom, in the given byte order or the original order if `None`.
+    ///
+    /// The header is written as-is, and its CRC values are not recomputed. If the body was edited
+    /// with `data_mut`, use `write_corrected` or call `correct_crc` first.
     pub fn write<'a, T: Write>(&self, writer: &'a mut T, endianness: Option<&Endianness>) -> io::Result<usize> {
         let order = match endianness {
             // Use endianness if specified
@@ -187,15 +736,41 @@ impl Rom {
         // Wrap in writer that respects chosen byte order
         let mut writer = Writer::from(writer, *order);
 
-        // Write header, IPL3 and data
+        // Write header, IPL3 and data, failing with `WriteZero` if the writer stops accepting data
         let mut written = self.header.write(&mut writer)?;
         written += self.ipl3.write(&mut writer)?;
-        written += writer.write(&self.image[HEAD_SIZE..])?;
+        writer.write_all(&self.image[HEAD_SIZE..])?;
+        written += self.image.len() - HEAD_SIZE;
         writer.flush()?;
+        Ok(written)
+    }
 
-        // Todo: Compare total amount written to expected length
+    /// Write the full rom to a file at the given path, which is created or truncated.
+    ///
+    /// The byte order defaults to the original order, as with `write`.
+    pub fn save(&self, path: impl AsRef<Path>, endianness: Option<Endianness>) -> io::Result<usize> {
+        let mut file = File::create(path)?;
+        self.write(&mut file, endianness.as_ref())
+    }
 
-        Ok(written)
+    /// Correct the header CRC values, then write the full rom as with `write`.
+    pub fn write_corrected<T: Write>(&mut self, writer: &'_ mut T, endianness: Option<&Endianness>) -> io::Result<usize> {
+        self.correct_crc();
+        self.write(writer, endianness)
+    }
+
+    /// Get a copy of this rom in the given byte order, leaving this rom untouched.
+    ///
+    /// The image data is held in big-endian order regardless of the byte order, so the copy holds
+    /// the same image and differs only in the byte order used by `write` and `body_reader`.
+    pub fn to_order(&self, target: Endianness) -> Result<Rom, Error> {
+        // Only whole words can be converted from big-endian
+        if target != Endianness::Big && self.image.len() % 4 != 0 {
+            return Err(crate::convert::Error::AlignmentError(self.image.len()).into());
+        }
+        let mut rom = self.clone();
+        rom.order = target;
+        Ok(rom)
     }
 
     pub fn len(&self) -> usize {
@@ -206,3 +781,627 @@ impl Rom {
         self.len() == 0
     }
 }
+
+/// Check whether or not the data begins with a copier header, followed by a valid rom magic number.
+pub fn has_copier_header(data: &[u8]) -> bool {
+    data.len() >= COPIER_HEADER_SIZE + Magic::SIZE
+        && Magic::infer_byte_order(&data[..Magic::SIZE]).is_err()
+        && Magic::infer_byte_order(&data[COPIER_HEADER_SIZE..]).is_ok()
+}
+
+/// Verify the CRC values of a rom read from a reader, without reading the entire body into memory.
+///
+/// Returns whether or not the CRC values are correct, along with the computed CRC values.
+pub fn verify_reader<T: Read>(mut reader: &mut T) -> Result<(bool, (u32, u32)), Error> {
+    // Read header & infer endianness
+    let (header, order) = Header::read_ordered(&mut reader)?;
+
+    // Read IPL3 and stream the remaining data through the CRC computation
+    let mut reader = Reader::from(&mut reader, order);
+    let ipl3 = IPL3::read(&mut reader)?;
+    let calc = ipl3.compute_crcs_reader(&mut reader)?;
+    Ok((header.crcs() == calc, calc))
+}
+
+/// Verify the CRC values of a rom file, without reading the entire body into memory.
+///
+/// Returns whether or not the CRC values are correct, along with the computed CRC values.
+pub fn verify_path(path: impl AsRef<Path>) -> Result<(bool, (u32, u32)), Error> {
+    let mut file = File::open(path)?;
+    verify_reader(&mut file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a big-endian `Rom` with an empty body.
+    fn empty_rom() -> Rom {
+        let ipl3 = IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]);
+        let header = Header::new(0x8000_0400, "N64ROM TEST", b"NTEE", &[], &[], &ipl3).unwrap();
+        let mut image = Vec::new();
+        header.write(&mut image).unwrap();
+        ipl3.write(&mut image).unwrap();
+        Rom::from(header, ipl3, image, Endianness::Big)
+    }
+
+    /// Create a raw big-endian image with distinct header fields and incorrect CRC values.
+    fn raw_image() -> Vec<u8> {
+        let mut image = vec![0; HEAD_SIZE + 0x2000];
+        image[..4].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+        // Clock rate, entry point and release.
+        image[0x04..0x08].copy_from_slice(&[0x00, 0x00, 0x00, 0x0F]);
+        image[0x08..0x0C].copy_from_slice(&[0x80, 0x00, 0x04, 0x00]);
+        image[0x0C..0x10].copy_from_slice(&[0x00, 0x00, 0x14, 0x4B]);
+        // Bogus CRC values.
+        image[0x10..0x18].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF, 0xDE, 0xAD, 0xBE, 0xEF]);
+        image[0x20..0x34].copy_from_slice(b"N64ROM TEST         ");
+        image[0x3B..0x3F].copy_from_slice(b"NTEE");
+        for (i, byte) in image[HEAD_SIZE..].iter_mut().enumerate() {
+            *byte = (i * 7) as u8;
+        }
+        image
+    }
+
+    #[test]
+    fn detect_restores_position() {
+        let mut data = vec![0xFF; 4];
+        data.extend(crate::convert::convert_vec(raw_image(), Endianness::Big, Endianness::Mixed).unwrap());
+        let mut cursor = io::Cursor::new(data);
+        cursor.set_position(4);
+        assert_eq!(Endianness::detect(&mut cursor).unwrap(), Endianness::Mixed);
+        assert_eq!(cursor.position(), 4);
+
+        // The position is restored when the magic number is unknown or cut short
+        cursor.set_position(0);
+        assert!(Endianness::detect(&mut cursor).is_err());
+        assert_eq!(cursor.position(), 0);
+        cursor.set_position(cursor.get_ref().len() as u64 - 2);
+        assert!(Endianness::detect(&mut cursor).is_err());
+        assert_eq!(cursor.position(), cursor.get_ref().len() as u64 - 2);
+    }
+
+    #[test]
+    fn correct_crc_preserves_header_fields() {
+        for &order in &[Endianness::Big, Endianness::Little, Endianness::Mixed] {
+            let original = raw_image();
+            let mut file = original.clone();
+            crate::convert::convert(&mut file, Endianness::Big, order).unwrap();
+
+            let mut rom = Rom::read(&mut &file[..]).unwrap();
+            assert!(!rom.correct_crc());
+            let mut output = Vec::new();
+            rom.write(&mut output, None).unwrap();
+            crate::convert::convert(&mut output, order, Endianness::Big).unwrap();
+
+            // Only the CRC values may change.
+            assert_eq!(output.len(), original.len());
+            assert_eq!(&output[..0x10], &original[..0x10]);
+            assert_eq!(&output[0x18..], &original[0x18..]);
+            let (crc1, crc2) = rom.header.crcs();
+            assert_eq!(&output[0x10..0x14], &crc1.to_be_bytes());
+            assert_eq!(&output[0x14..0x18], &crc2.to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn verify_report_status() {
+        let mut rom = empty_rom();
+        rom.ipl3 = IPL3::from_cic(Cic::Unknown, *rom.ipl3.get_ipl());
+        rom.correct_crc();
+        let report = rom.verify();
+        assert_eq!(report.status, VerifyStatus::UnknownCic);
+        assert_eq!(report.cic, Cic::Unknown);
+        assert_eq!(report.stored, report.computed);
+
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6102, *rom.ipl3.get_ipl());
+        rom.correct_crc();
+        let report = rom.verify();
+        assert_eq!(report.status, VerifyStatus::Ok);
+        assert_eq!(report.cic, Cic::Cic6102);
+        assert_eq!(report.stored, report.computed);
+
+        rom.header.crc1 ^= 1;
+        let report = rom.verify();
+        assert_eq!(report.status, VerifyStatus::CrcMismatch);
+        assert_eq!(report.stored, (report.computed.0 ^ 1, report.computed.1));
+    }
+
+    #[test]
+    fn verify_reader_matches_check_crc() {
+        let image = raw_image();
+        let mut rom = Rom::read(&mut &image[..]).unwrap();
+        let (result, calc) = verify_reader(&mut &image[..]).unwrap();
+        assert_eq!((result, calc), rom.check_crc());
+        assert!(!result);
+
+        rom.correct_crc();
+        let mut output = Vec::new();
+        rom.write(&mut output, Some(&Endianness::Mixed)).unwrap();
+        assert_eq!(verify_reader(&mut &output[..]).unwrap(), (true, calc));
+    }
+
+    #[test]
+    fn recompute_all_fields() {
+        let mut rom = empty_rom();
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6106, *rom.ipl3.get_ipl());
+        rom.header.entry_point = rom.ipl3.offset(0x8000_0400);
+        rom.body_writer().write_all(&[0x12; 0x400]).unwrap();
+        rom.header.crc1 = 0;
+        let (name, media) = (*rom.header.name(), *rom.header.media());
+
+        // The zeroed bootcode in the image is detected as unknown, which has no entry point offset
+        rom.recompute_all();
+        assert_eq!(rom.cic(), Cic::Unknown);
+        assert_eq!(rom.header.entry_point, 0x8000_0400);
+        assert!(rom.check_crc().0);
+        assert_eq!(rom.header.name(), &name);
+        assert!(rom.header.media() == &media);
+    }
+
+    #[test]
+    fn error_messages_name_the_operation() {
+        let mut image = raw_image();
+        crate::convert::convert(&mut image, Endianness::Big, Endianness::Little).unwrap();
+        image.push(0);
+        let err = Rom::from_image(image).err().unwrap();
+        assert!(err.to_string().starts_with("Unable to convert rom data to big-endian: "));
+
+        let err = Rom::from_image(vec![0; HEAD_SIZE]).err().unwrap();
+        assert!(err.to_string().starts_with("Unable to read or build rom header: "));
+    }
+
+    #[test]
+    fn from_image_byte_swapped() {
+        let image = raw_image();
+        for &order in &[Endianness::Mixed, Endianness::Little] {
+            let mut file = image.clone();
+            crate::convert::convert(&mut file, Endianness::Big, order).unwrap();
+            let rom = Rom::from_image(file.clone()).unwrap();
+            assert_eq!(rom.order(), order);
+            assert_eq!(rom.header.name_str().unwrap(), "N64ROM TEST         ");
+            assert_eq!(rom.header.media().as_str().unwrap(), "NTEE");
+            assert_eq!(rom.header.crcs(), (0xDEAD_BEEF, 0xDEAD_BEEF));
+            assert_eq!(rom.full(), &image[..]);
+            assert!(rom == Rom::from_image(image.clone()).unwrap());
+
+            // Writing in the original order round-trips to the source data.
+            let mut output = Vec::new();
+            rom.write(&mut output, None).unwrap();
+            assert_eq!(output, file);
+        }
+    }
+
+    #[test]
+    fn region_from_header() {
+        let image = raw_image();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        assert_eq!(rom.region_code(), 'E');
+        assert_eq!(rom.region(), Region::NorthAmerica);
+    }
+
+    #[test]
+    fn media_region_mismatch() {
+        let image = raw_image();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        assert_eq!(rom.media_region_mismatch_warning(), None);
+
+        let mut image = raw_image();
+        image[0x3E] = b'Q';
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        assert!(rom.media_region_mismatch_warning().is_some());
+
+        let mut image = raw_image();
+        image[0x3B] = 0;
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        assert!(rom.media_region_mismatch_warning().is_some());
+    }
+
+    #[test]
+    fn head_and_boot_includes_body_prefix() {
+        let image = raw_image();
+        let mut rom = Rom::read(&mut &image[..]).unwrap();
+        rom.correct_crc();
+
+        let blob = rom.head_and_boot(0x100);
+        assert_eq!(blob.len(), HEAD_SIZE + 0x100);
+        // The corrected CRC values are included, rather than the stale values in the image.
+        assert_eq!(Header::read(&mut &blob[..]).unwrap().crcs(), rom.header.crcs());
+        assert_eq!(&blob[Header::SIZE..HEAD_SIZE], &image[Header::SIZE..HEAD_SIZE]);
+        assert_eq!(&blob[HEAD_SIZE..], &image[HEAD_SIZE..HEAD_SIZE + 0x100]);
+
+        // Requesting more than the body holds returns the entire image.
+        assert_eq!(rom.head_and_boot(usize::MAX).len(), image.len());
+    }
+
+    #[test]
+    fn ipl3_bytes_match_ipl3() {
+        let mut image = raw_image();
+        for (i, byte) in image[Header::SIZE..HEAD_SIZE].iter_mut().enumerate() {
+            *byte = (i * 3) as u8;
+        }
+        crate::convert::convert(&mut image, Endianness::Big, Endianness::Mixed).unwrap();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        assert_eq!(rom.ipl3_bytes().len(), IPL_SIZE);
+        assert_eq!(rom.ipl3_bytes(), &rom.ipl3.get_ipl()[..]);
+        assert_eq!(rom.ipl3_bytes()[..4], [0, 3, 6, 9]);
+    }
+
+    #[test]
+    fn new_from_program() {
+        let ipl3 = IPL3::from_cic(Cic::Cic6105, [0x11; IPL_SIZE]);
+        let program: Vec<u8> = (0..0x3001).map(|i| (i * 5) as u8).collect();
+        let fs = [0xA5; 0x800];
+        let rom = Rom::new(ipl3, 0x8000_0400, "NEW ROM", b"NNRE", program.clone(), &fs).unwrap();
+        assert_eq!(rom.order(), Endianness::Big);
+        assert_eq!(rom.len(), HEAD_SIZE + program.len() + 1 + fs.len());
+        assert_eq!(rom.fs_offset(), Some(program.len() + 1));
+        assert_eq!(rom.data()[program.len()], 0);
+        assert_eq!(rom.header.crcs(), ipl3.compute_crcs(&program, &fs));
+        assert_eq!(rom.entry_point(), 0x8000_0400);
+        assert_eq!(rom.header.name_str().unwrap(), "NEW ROM             ");
+
+        assert!(rom.check_crc().0);
+
+        // The written rom reads back with the same CRC values
+        let mut output = Vec::new();
+        rom.write(&mut output, None).unwrap();
+        let mut reread = Rom::read(&mut &output[..]).unwrap();
+        assert!(reread == rom);
+        assert_eq!(reread.header.crcs(), rom.header.crcs());
+
+        // The synthetic bootcode is not recognized, so restore the variant before checking. The fs
+        // offset is not stored in the file, so the CRC values must match the contiguous image.
+        assert_eq!(reread.fs_offset(), None);
+        reread.ipl3 = IPL3::from_cic(Cic::Cic6105, *reread.ipl3.get_ipl());
+        assert!(reread.check_crc().0);
+
+        let result = Rom::new(ipl3, 0x8000_0400, "NEW ROM", b"NNR", program, &fs);
+        assert!(matches!(result, Err(Error::HeaderError(crate::header::Error::MediaLength(3)))));
+    }
+
+    #[test]
+    fn write_head_in_order() {
+        let image = raw_image();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        let mut output = Vec::new();
+        assert_eq!(rom.write_head(&mut output, Some(&Endianness::Mixed)).unwrap(), HEAD_SIZE);
+        let expected = crate::convert::convert_vec(image[..HEAD_SIZE].to_vec(), Endianness::Big, Endianness::Mixed).unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn write_returns_image_length() {
+        let image = raw_image();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        let mut output = Vec::new();
+        let written = rom.write(&mut output, None).unwrap();
+        assert_eq!(written, image.len());
+        assert_eq!(output.len(), image.len());
+    }
+
+    #[test]
+    fn write_corrected_updates_crcs() {
+        let image = raw_image();
+        let mut rom = Rom::read(&mut &image[..]).unwrap();
+        rom.correct_crc();
+        rom.data_mut()[0] ^= 0xFF;
+
+        // Plain write keeps the stale CRC values.
+        let mut output = Vec::new();
+        rom.write(&mut output, None).unwrap();
+        assert!(!verify_reader(&mut &output[..]).unwrap().0);
+
+        let mut output = Vec::new();
+        rom.write_corrected(&mut output, Some(&Endianness::Little)).unwrap();
+        let (result, (calc1, calc2)) = verify_reader(&mut &output[..]).unwrap();
+        assert!(result);
+        assert_eq!(rom.header.crcs(), (calc1, calc2));
+    }
+
+    #[test]
+    fn write_to_capped_writer_fails() {
+        /// Writer which accepts only a limited amount of bytes, then writes nothing.
+        struct CappedWriter(Vec<u8>, usize);
+
+        impl Write for CappedWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let amount = buf.len().min(self.1 - self.0.len());
+                self.0.extend_from_slice(&buf[..amount]);
+                Ok(amount)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let image = raw_image();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        for &order in &[Endianness::Big, Endianness::Little] {
+            let mut output = CappedWriter(Vec::new(), image.len() - 0x100);
+            let err = rom.write(&mut output, Some(&order)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+            assert_eq!(output.0.len(), image.len() - 0x100);
+        }
+    }
+
+    #[test]
+    fn eq_ignores_byte_order() {
+        let image = raw_image();
+        let mut swapped = image.clone();
+        crate::convert::convert(&mut swapped, Endianness::Big, Endianness::Little).unwrap();
+
+        let big = Rom::read(&mut &image[..]).unwrap();
+        let mut little = Rom::read(&mut &swapped[..]).unwrap();
+        assert_eq!(little.order(), Endianness::Little);
+        assert!(big == little);
+
+        little.data_mut()[0] ^= 0xFF;
+        assert!(big != little);
+    }
+
+    #[test]
+    fn to_order_leaves_original() {
+        let image = raw_image();
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        let little = rom.to_order(Endianness::Little).unwrap();
+        assert_eq!(little.order(), Endianness::Little);
+        assert_eq!(rom.order(), Endianness::Big);
+        assert_eq!(rom.full(), &image[..]);
+
+        let mut expected = image.clone();
+        crate::convert::convert(&mut expected, Endianness::Big, Endianness::Little).unwrap();
+        let mut output = Vec::new();
+        little.write(&mut output, None).unwrap();
+        assert_eq!(output, expected);
+
+        let mut unaligned = rom.clone();
+        unaligned.image.push(0);
+        assert!(unaligned.to_order(Endianness::Mixed).is_err());
+    }
+
+    #[test]
+    fn is_homebrew_heuristic() {
+        let mut rom = empty_rom();
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6102, [0; IPL_SIZE]);
+        rom.image.resize(MEBIBYTE as usize * 8, 0);
+        assert!(!rom.is_homebrew());
+
+        // Small with a placeholder game code.
+        rom.header = Header::template(0x8000_0400, [0x20; 20], *b"N\0\0E");
+        rom.image.truncate(MEBIBYTE as usize);
+        assert!(rom.is_homebrew());
+
+        // A single signal is not enough.
+        rom.header = Header::template(0x8000_0400, [0x20; 20], *b"NTEE");
+        assert!(!rom.is_homebrew());
+        rom.ipl3 = IPL3::from_cic(Cic::Unknown, [0; IPL_SIZE]);
+        assert!(rom.is_homebrew());
+    }
+
+    #[test]
+    fn is_truncated_heuristic() {
+        let mut rom = empty_rom();
+        rom.image.resize(MEBIBYTE as usize * 2, 0);
+        assert!(!rom.is_truncated());
+
+        // Within the CRC region.
+        rom.image.truncate(HEAD_SIZE + 0x1000);
+        assert!(rom.is_truncated());
+
+        // Cut off abruptly, versus ending in padding.
+        rom.image.resize(MEBIBYTE as usize + 0x1234, 0x5A);
+        assert!(rom.is_truncated());
+        rom.image.resize(MEBIBYTE as usize + 0x2000, 0xFF);
+        assert!(!rom.is_truncated());
+    }
+
+    #[test]
+    fn guess_cic_from_crcs() {
+        let image = raw_image();
+        let mut rom = Rom::read(&mut &image[..]).unwrap();
+        assert_eq!(rom.guess_cic(), None);
+
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6103, *rom.ipl3.get_ipl());
+        rom.correct_crc();
+        rom.ipl3 = IPL3::from_cic(Cic::Unknown, *rom.ipl3.get_ipl());
+        assert_eq!(rom.guess_cic(), Some(Cic::Cic6103));
+
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6101, *rom.ipl3.get_ipl());
+        rom.correct_crc();
+        assert_eq!(rom.guess_cic(), Some(Cic::Cic6102));
+    }
+
+    #[test]
+    fn copier_header() {
+        let image = raw_image();
+        assert!(!has_copier_header(&image));
+        let mut copier = vec![0; COPIER_HEADER_SIZE];
+        copier.extend(&image);
+        assert!(has_copier_header(&copier));
+    }
+
+    #[test]
+    fn goodn64_id_format() {
+        let mut rom = empty_rom();
+        rom.header.crc1 = 0x635A_2BFF;
+        rom.header.crc2 = 0x8B02_2326;
+        assert_eq!(rom.goodn64_id(), "N64ROM TEST (E) [635A2BFF-8B022326]");
+    }
+
+    #[test]
+    fn entry_point_offset_by_cic() {
+        let mut rom = empty_rom();
+        rom.set_entry_point(0x8000_0400);
+        assert_eq!(rom.header.entry_point, 0x8000_0400);
+        assert_eq!(rom.entry_point(), 0x8000_0400);
+
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6103, [0; IPL_SIZE]);
+        rom.set_entry_point(0x8000_0400);
+        assert_eq!(rom.header.entry_point, 0x8010_0400);
+        assert_eq!(rom.entry_point(), 0x8000_0400);
+
+        rom.ipl3 = IPL3::from_cic(Cic::Cic6106, [0; IPL_SIZE]);
+        rom.set_entry_point(0x8000_0400);
+        assert_eq!(rom.header.entry_point, 0x8020_0400);
+        assert_eq!(rom.entry_point(), 0x8000_0400);
+    }
+
+    #[test]
+    fn set_fs_offset_requires_alignment() {
+        let image = raw_image();
+        let mut rom = Rom::read(&mut &image[..]).unwrap();
+        let contiguous = rom.check_crc().1;
+
+        rom.set_fs_offset(Some(0x1000)).unwrap();
+        assert_eq!(rom.fs_offset(), Some(0x1000));
+        let (program, fs) = rom.data().split_at(0x1000);
+        assert_eq!(rom.ipl3.compute_crcs(program, fs), contiguous);
+        assert_eq!(rom.check_crc().1, contiguous);
+
+        let result = rom.set_fs_offset(Some(0x1001));
+        assert!(matches!(result, Err(Error::FsOffsetAlignment(0x1001))));
+        assert_eq!(rom.fs_offset(), Some(0x1000));
+
+        rom.set_fs_offset(None).unwrap();
+        assert_eq!(rom.fs_offset(), None);
+        assert_eq!(rom.check_crc().1, contiguous);
+    }
+
+    #[test]
+    fn set_fs_region() {
+        let mut rom = empty_rom();
+        rom.image.extend(&[0x11; 0x101]);
+        let fs = vec![0x22; 0x80];
+        assert_eq!(rom.set_fs(fs.clone(), 0x200), 0x200);
+        assert_eq!(rom.fs_offset(), Some(0x200));
+        assert_eq!(rom.data().len(), 0x280);
+        assert_eq!(&rom.data()[0x101..0x200], &[0; 0xFF][..]);
+        assert_eq!(&rom.data()[0x200..], &fs[..]);
+
+        let calc = rom.ipl3.compute_crcs(&rom.data()[..0x200], &fs);
+        assert_eq!(rom.check_crc().1, calc);
+        assert!(!rom.correct_crc());
+        assert!(rom.check_crc().0);
+        assert_eq!(rom.guess_cic(), Some(Cic::Cic6102));
+
+        // An odd offset is rounded up, storing the pad byte, so the CRC values match the image
+        assert_eq!(rom.set_fs(fs.clone(), 0x201), 0x202);
+        assert_eq!(rom.data().len(), 0x282);
+        assert_eq!(&rom.data()[0x202..], &fs[..]);
+        rom.correct_crc();
+        rom.set_fs_offset(None).unwrap();
+        assert!(rom.check_crc().0);
+    }
+
+    #[test]
+    fn fix_magic_repairs_corrupt_magic() {
+        let mut image = raw_image();
+        image[..4].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        assert!(Rom::read(&mut &image[..]).is_err());
+
+        let mut rom = Rom::read_with_order(&mut &image[..], Endianness::Big, true).unwrap();
+        rom.fix_magic();
+        assert_eq!(rom.header.magic().to_u32(), 0x8037_1240);
+        assert_eq!(&rom.full()[..4], &[0x80, 0x37, 0x12, 0x40]);
+        assert_eq!(rom.data(), &image[HEAD_SIZE..]);
+    }
+
+    #[test]
+    fn trim_overdump_to_power_of_two() {
+        const MIB: usize = 1024 * 1024;
+        let mut rom = empty_rom();
+        rom.image.resize(3 * MIB, 0x12);
+        rom.image.resize(6 * MIB, 0xFF);
+        assert_eq!(rom.overdump_len(), 2 * MIB);
+        assert_eq!(rom.trim_overdump(), 2 * MIB);
+        assert_eq!(rom.len(), 4 * MIB);
+        assert_eq!(rom.trim_overdump(), 0);
+
+        // Data filling the power-of-two size is kept.
+        let mut rom = empty_rom();
+        rom.image.resize(4 * MIB, 0x12);
+        rom.image.resize(8 * MIB, 0xFF);
+        assert_eq!(rom.trim_overdump(), 4 * MIB);
+        assert_eq!(rom.len(), 4 * MIB);
+    }
+
+    #[test]
+    #[cfg(feature = "hashes")]
+    fn hashes_of_known_data() {
+        let mut rom = empty_rom();
+        rom.image = b"abc".to_vec();
+        assert_eq!(rom.md5()[..4], [0x90, 0x01, 0x50, 0x98]);
+        assert_eq!(rom.sha1()[..4], [0xa9, 0x99, 0x3e, 0x36]);
+    }
+
+    #[test]
+    fn pad_to_power_of_two() {
+        let mut rom = empty_rom();
+        rom.image.resize(0x3000, 0);
+        assert_eq!(rom.pad_to_power_of_two(0xFF), 0x1000);
+        assert_eq!(rom.len(), 0x4000);
+        assert_eq!(rom.full()[0x3000..], [0xFF; 0x1000][..]);
+        assert_eq!(rom.pad_to_power_of_two(0xFF), 0);
+    }
+
+    #[test]
+    fn pad_to_size() {
+        let mut rom = empty_rom();
+        rom.image.resize(3 * MEBIBYTE as usize, 0x12);
+        let head = rom.full()[..HEAD_SIZE].to_vec();
+        assert_eq!(rom.pad_to(4 * MEBIBYTE as usize, 0xFF).unwrap(), MEBIBYTE as usize);
+        assert_eq!(rom.len(), 4 * MEBIBYTE as usize);
+        assert_eq!(&rom.full()[..HEAD_SIZE], &head[..]);
+        assert!(rom.full()[3 * MEBIBYTE as usize..].iter().all(|&b| b == 0xFF));
+        assert_eq!(rom.data()[3 * MEBIBYTE as usize - HEAD_SIZE - 1], 0x12);
+
+        assert_eq!(rom.pad_to(rom.len(), 0).unwrap(), 0);
+        assert!(matches!(rom.pad_to(MEBIBYTE as usize, 0), Err(Error::PadSizeError(_, _))));
+        assert_eq!(rom.len(), 4 * MEBIBYTE as usize);
+    }
+
+    #[test]
+    fn trim_overdump_keeps_crc_region() {
+        let mut rom = empty_rom();
+        rom.image.resize(HEAD_SIZE + 0x1000, 0x12);
+        rom.image.resize(4 * 1024 * 1024, 0x00);
+        assert_eq!(rom.trim_overdump(), 2 * 1024 * 1024);
+    }
+
+    #[test]
+    fn body_reader_uses_stored_order() {
+        let image = raw_image();
+        for &order in &[Endianness::Big, Endianness::Little, Endianness::Mixed] {
+            let mut file = image.clone();
+            crate::convert::convert(&mut file, Endianness::Big, order).unwrap();
+            let rom = Rom::read(&mut &file[..]).unwrap();
+
+            let mut body = Vec::new();
+            rom.body_reader().read_to_end(&mut body).unwrap();
+            assert_eq!(body, &file[HEAD_SIZE..]);
+        }
+    }
+
+    #[test]
+    fn body_writer_appends_and_corrects_crc() {
+        let mut rom = empty_rom();
+        let program: Vec<u8> = (0..0x1000).map(|i| i as u8).collect();
+        {
+            let mut writer = rom.body_writer();
+            writer.write_all(&program[..0x800]).unwrap();
+            writer.write_all(&program[0x800..]).unwrap();
+            writer.flush().unwrap();
+        }
+        assert_eq!(rom.data(), &program[..]);
+        assert_eq!(rom.header.crcs(), rom.ipl3.compute_crcs(&program, &[]));
+    }
+
+    #[test]
+    fn body_writer_corrects_crc_on_drop() {
+        let mut rom = empty_rom();
+        rom.body_writer().write_all(&[1, 2, 3, 4]).unwrap();
+        assert!(rom.check_crc().0);
+    }
+}
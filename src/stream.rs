@@ -1,4 +1,4 @@
-use std::io::{Read, Result, Write};
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom, Write};
 
 use crate::convert;
 use crate::rom::Endianness;
@@ -9,9 +9,18 @@ const BUFFER_SIZE: usize = 1024 * 16;
 // Assert default buffer size is divisible by 4.
 const_assert_eq!(BUFFER_SIZE % 4, 0);
 
+/// Convert the byte order of a buffer, reporting conversion errors as `InvalidData` io errors.
+fn convert_buffer(buf: &mut [u8], current: Endianness, target: Endianness) -> Result<()> {
+    convert::convert(buf, current, target)
+        .map(|_| ())
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
+
 /// Reader for translating data from a base `Endianness` into `Endianness::Big` format.
 pub struct Reader<'r, T: Read> {
     buffer: Vec<u8>,
+    /// Amount of unconverted bytes of a partial word following the converted data.
+    carry: usize,
     endianness: Endianness,
     idx: usize,
     length: usize,
@@ -26,6 +35,7 @@ impl<'r, T: Read> Reader<'r, T> {
     pub fn with_buffer_size(reader: &'r mut T, endianness: Endianness, capacity: usize) -> Self {
         Self {
             buffer: vec![0; capacity],
+            carry: 0,
             endianness,
             idx: 0,
             length: 0,
@@ -41,9 +51,38 @@ impl<'r, T: Read> Reader<'r, T> {
     }
 
     /// Refill the contents of the buffer and reset the index to 0.
+    ///
+    /// Only complete words are converted, a trailing partial word is carried into the next refill.
     fn refill(&mut self) -> Result<usize> {
-        let length = self.reader.read(&mut *self.buffer)?;
-        convert::convert(&mut self.buffer[..length], self.endianness, Endianness::Big).unwrap();
+        // Move the carried partial word to the start of the buffer
+        let mut total = self.carry;
+        self.buffer.copy_within(self.length..self.length + total, 0);
+
+        // Read until at least one complete word is available, or the end of data is reached
+        let mut eof = false;
+        while total < 4 {
+            let amount = self.reader.read(&mut self.buffer[total..])?;
+            if amount == 0 {
+                eof = true;
+                break;
+            }
+            total += amount;
+        }
+
+        let length = if eof {
+            // A partial word at the end of data can only be passed through if no conversion is needed
+            if total % 4 != 0 && self.endianness != Endianness::Big {
+                return Err(Error::new(ErrorKind::InvalidData, "Data ends with a partial word which cannot be converted"));
+            }
+            total
+        } else {
+            total - (total % 4)
+        };
+
+        if self.endianness != Endianness::Big {
+            convert_buffer(&mut self.buffer[..length], self.endianness, Endianness::Big)?;
+        }
+        self.carry = total - length;
         self.idx = 0;
         self.length = length;
         Ok(length)
@@ -88,6 +127,53 @@ impl<'r, T: Read> Read for Reader<'r, T> {
     }
 }
 
+impl<'r, T: Read + Seek> Seek for Reader<'r, T> {
+    /// Seek the underlying reader, discarding any buffered data.
+    ///
+    /// Unless the base `Endianness` is big-endian, data can only be converted in whole words, so
+    /// seeking to an offset which is not 4-byte aligned returns an error of kind `InvalidInput` and
+    /// leaves the reader unchanged. Querying the position with `SeekFrom::Current(0)` always
+    /// succeeds and keeps the buffered data.
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        // The underlying reader is ahead of the current position by the buffered data
+        let inner = self.reader.stream_position()?;
+        let current = inner - (self.remaining() + self.carry) as u64;
+
+        let target = match pos {
+            SeekFrom::Current(0) => return Ok(current),
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => checked_offset(current, offset),
+            SeekFrom::End(offset) => {
+                // The end is only known to the underlying reader, so restore it afterwards
+                let end = self.reader.seek(SeekFrom::End(0))?;
+                self.reader.seek(SeekFrom::Start(inner))?;
+                checked_offset(end, offset)
+            }
+        };
+        let target = target.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Unable to seek to a negative or overflowing offset"))?;
+        if self.endianness != Endianness::Big && target % 4 != 0 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Unable to seek to an offset which is not 4-byte aligned"));
+        }
+
+        let position = self.reader.seek(SeekFrom::Start(target))?;
+
+        // Invalidate the buffer, which no longer follows the position of the underlying reader
+        self.carry = 0;
+        self.idx = 0;
+        self.length = 0;
+        Ok(position)
+    }
+}
+
+/// Apply a signed offset to a position, returning `None` if the result would be out of range.
+fn checked_offset(position: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        position.checked_add(offset as u64)
+    } else {
+        position.checked_sub(offset.unsigned_abs())
+    }
+}
+
 /// Writer for translating data from `Endianness::Big` into a base `Endianness` format.
 pub struct Writer<'w, T: Write> {
     buffer: Vec<u8>,
@@ -101,7 +187,9 @@ impl<'w, T: Write> Writer<'w, T> {
         Self::with_buffer_size(writer, endianness, BUFFER_SIZE)
     }
 
+    /// Create with a buffer of the given capacity, which must be able to hold at least one word.
     pub fn with_buffer_size(writer: &'w mut T, endianness: Endianness, capacity: usize) -> Self {
+        assert!(capacity >= 4, "Writer buffer capacity must be at least 4 bytes");
         Self {
             buffer: vec![0; capacity],
             endianness,
@@ -118,12 +206,22 @@ impl<'w, T: Write> Writer<'w, T> {
     }
 
     /// Flush buffer without flushing the underlying writer.
-    fn buf_flush(&mut self) -> Result<()> {
-        convert::convert(&mut self.buffer[..self.length], Endianness::Big, self.endianness).unwrap();
-        let data = &self.buffer[..self.length];
-        self.writer.write_all(data)?;
-        self.length = 0;
-        Ok(())
+    ///
+    /// Only complete words are converted, a trailing partial word is moved to the start of the
+    /// buffer to be completed by a later write. Returns the amount of bytes still buffered.
+    fn buf_flush(&mut self) -> Result<usize> {
+        let length = if self.endianness == Endianness::Big {
+            self.length
+        } else {
+            self.length - (self.length % 4)
+        };
+        if self.endianness != Endianness::Big {
+            convert_buffer(&mut self.buffer[..length], Endianness::Big, self.endianness)?;
+        }
+        self.writer.write_all(&self.buffer[..length])?;
+        self.buffer.copy_within(length..self.length, 0);
+        self.length -= length;
+        Ok(self.length)
     }
 
     fn remaining(&self) -> usize {
@@ -155,8 +253,229 @@ impl<'w, T: Write> Write for Writer<'w, T> {
         Ok(written)
     }
 
+    /// Flush all complete words to the underlying writer.
+    ///
+    /// If a partial word remains which cannot yet be converted, it stays buffered and an error of
+    /// kind `InvalidData` is returned. A later write may complete the word.
     fn flush(&mut self) -> Result<()> {
-        self.buf_flush()?;
-        self.writer.flush()
+        let carry = self.buf_flush()?;
+        self.writer.flush()?;
+        if carry == 0 {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::InvalidData, "Unable to flush a partial word which cannot be converted"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader which returns data in chunks of varying lengths.
+    struct ChunkedReader<'a> {
+        data: &'a [u8],
+        lengths: std::iter::Cycle<std::slice::Iter<'a, usize>>,
+    }
+
+    impl<'a> ChunkedReader<'a> {
+        fn new(data: &'a [u8], lengths: &'a [usize]) -> Self {
+            Self {
+                data,
+                lengths: lengths.iter().cycle(),
+            }
+        }
+    }
+
+    impl<'a> Read for ChunkedReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let length = *self.lengths.next().unwrap();
+            let length = length.min(buf.len()).min(self.data.len());
+            buf[..length].copy_from_slice(&self.data[..length]);
+            self.data = &self.data[length..];
+            Ok(length)
+        }
+    }
+
+    fn test_data(length: usize) -> Vec<u8> {
+        (0..length).map(|i| (i * 31) as u8).collect()
+    }
+
+    #[test]
+    fn reader_carries_partial_words() {
+        let expected = test_data(0x10000);
+        for &order in &[Endianness::Little, Endianness::Mixed] {
+            let mut data = expected.clone();
+            convert::convert(&mut data, Endianness::Big, order).unwrap();
+
+            let mut inner = ChunkedReader::new(&data, &[17, 3, 4096]);
+            let mut reader = Reader::from(&mut inner, order);
+            let mut output = Vec::new();
+            reader.read_to_end(&mut output).unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn reader_three_bytes_at_a_time() {
+        let expected = test_data(0x1000);
+        for &order in &[Endianness::Little, Endianness::Mixed] {
+            let mut data = expected.clone();
+            convert::convert(&mut data, Endianness::Big, order).unwrap();
+
+            // Every refill of the small buffer ends within a word
+            let mut inner = ChunkedReader::new(&data, &[3]);
+            let mut reader = Reader::with_buffer_size(&mut inner, order, 10);
+            let mut output = Vec::new();
+            reader.read_to_end(&mut output).unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+
+    #[test]
+    fn reader_seek_aligned() {
+        let expected = test_data(0x100);
+        let mut data = expected.clone();
+        convert::convert(&mut data, Endianness::Big, Endianness::Little).unwrap();
+
+        let mut inner = std::io::Cursor::new(data);
+        let mut reader = Reader::with_buffer_size(&mut inner, Endianness::Little, 0x40);
+        let mut buf = [0; 8];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 8);
+        assert_eq!(reader.seek(SeekFrom::Current(0x18)).unwrap(), 0x20);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], expected[0x20..0x28]);
+
+        assert_eq!(reader.seek(SeekFrom::Start(0x80)).unwrap(), 0x80);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], expected[0x80..0x88]);
+        assert_eq!(reader.seek(SeekFrom::End(-8)).unwrap(), 0xF8);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest[..], expected[0xF8..]);
+    }
+
+    #[test]
+    fn reader_stream_position_after_partial_word() {
+        let mut inner = std::io::Cursor::new(vec![3, 2, 1, 0, 7, 6, 5, 4]);
+        let mut reader = Reader::from(&mut inner, Endianness::Little);
+        let mut buf = [0; 2];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.stream_position().unwrap(), 2);
+        let err = reader.seek(SeekFrom::Start(3)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = reader.seek(SeekFrom::End(-1)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn reader_seek_unaligned() {
+        let expected = test_data(0x100);
+        let mut data = expected.clone();
+        convert::convert(&mut data, Endianness::Big, Endianness::Mixed).unwrap();
+
+        let mut inner = std::io::Cursor::new(data);
+        let mut reader = Reader::from(&mut inner, Endianness::Mixed);
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        let err = reader.seek(SeekFrom::Current(2)).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], expected[4..8]);
+
+        // Big-endian data needs no conversion, so any offset is allowed
+        let mut inner = std::io::Cursor::new(expected.clone());
+        let mut reader = Reader::from(&mut inner, Endianness::Big);
+        assert_eq!(reader.seek(SeekFrom::Start(3)).unwrap(), 3);
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[..], expected[3..7]);
+    }
+
+    #[test]
+    fn reader_passes_through_unaligned_big_endian() {
+        let expected = test_data(0x1001);
+        let mut inner = ChunkedReader::new(&expected, &[17, 3, 4096]);
+        let mut reader = Reader::from(&mut inner, Endianness::Big);
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn reader_rejects_trailing_partial_word() {
+        let data = test_data(0x1001);
+        let mut inner = &data[..];
+        let mut reader = Reader::from(&mut inner, Endianness::Little);
+        let mut output = Vec::new();
+        let err = reader.read_to_end(&mut output).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn convert_buffer_unaligned_is_error() {
+        let mut data = test_data(7);
+        let err = convert_buffer(&mut data, Endianness::Big, Endianness::Little).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(data, test_data(7));
+        assert!(convert_buffer(&mut data[..4], Endianness::Big, Endianness::Little).is_ok());
+    }
+
+    #[test]
+    fn writer_carries_partial_words() {
+        let data = test_data(8);
+        let mut expected = data.clone();
+        convert::convert(&mut expected, Endianness::Big, Endianness::Mixed).unwrap();
+
+        let mut output = Vec::new();
+        let mut writer = Writer::from(&mut output, Endianness::Mixed);
+        writer.write_all(&data[..3]).unwrap();
+        writer.write_all(&data[3..]).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn writer_small_unaligned_buffer() {
+        let data = test_data(0x1001 - 1);
+        let mut expected = data.clone();
+        convert::convert(&mut expected, Endianness::Big, Endianness::Little).unwrap();
+
+        let mut output = Vec::new();
+        let mut writer = Writer::with_buffer_size(&mut output, Endianness::Little, 7);
+        for chunk in data.chunks(5) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn writer_passes_through_unaligned_big_endian() {
+        let data = test_data(0x1001);
+        let mut output = Vec::new();
+        let mut writer = Writer::with_buffer_size(&mut output, Endianness::Big, 7);
+        writer.write_all(&data).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn writer_flush_keeps_partial_word() {
+        let data = test_data(8);
+        let mut expected = data.clone();
+        convert::convert(&mut expected, Endianness::Big, Endianness::Mixed).unwrap();
+
+        let mut output = Vec::new();
+        let mut writer = Writer::from(&mut output, Endianness::Mixed);
+        writer.write_all(&data[..3]).unwrap();
+        assert_eq!(writer.flush().unwrap_err().kind(), ErrorKind::InvalidData);
+        writer.write_all(&data[3..]).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(output, expected);
     }
 }
@@ -1,5 +1,9 @@
+use std::fmt;
+use thiserror::Error;
+
 pub const KIBIBYTE: u64 = 1024;
 pub const MEBIBYTE: u64 = KIBIBYTE * 1024;
+pub const GIBIBYTE: u64 = MEBIBYTE * 1024;
 
 fn fdivide(length: u64, unit: u64) -> f64 {
     length as f64 / unit as f64
@@ -19,4 +23,127 @@ impl FileSize {
             Self::Float(result)
         }
     }
+
+    /// Format a length with the largest fitting unit out of bytes, KiB, MiB and GiB, such as
+    /// "1.5 GiB".
+    pub fn human(length: u64) -> String {
+        let (unit, suffix) = match length {
+            _ if length >= GIBIBYTE => (GIBIBYTE, "GiB"),
+            _ if length >= MEBIBYTE => (MEBIBYTE, "MiB"),
+            _ if length >= KIBIBYTE => (KIBIBYTE, "KiB"),
+            _ => (1, "B"),
+        };
+        format!("{} {}", Self::from(length, unit), suffix)
+    }
+}
+
+/// Shows integer values as-is, and other values with one decimal.
+impl fmt::Display for FileSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{:.1}", value),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseSizeError {
+    #[error("Invalid size {0:?}, expected a byte count optionally followed by a suffix such as M or Mi")]
+    InvalidNumber(String),
+    #[error("Unknown size suffix {0:?}, expected one of K, Ki, M, Mi, G or Gi")]
+    UnknownSuffix(String),
+    #[error("Size {0:?} is too large")]
+    Overflow(String),
+}
+
+/// Parse a human size such as "4Mi" or "512K" into an amount of bytes, the inverse of `FileSize`.
+///
+/// The suffixes `K`, `M` and `G` are decimal (powers of 1000), while `Ki`, `Mi` and `Gi` are binary
+/// (powers of 1024). A bare number is an amount of bytes.
+pub fn parse_size(value: &str) -> Result<u64, ParseSizeError> {
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (number, suffix) = value.split_at(split);
+    let unit = match suffix {
+        "" => 1,
+        "K" => 1000,
+        "Ki" => KIBIBYTE,
+        "M" => 1000 * 1000,
+        "Mi" => MEBIBYTE,
+        "G" => 1000 * 1000 * 1000,
+        "Gi" => GIBIBYTE,
+        _ if number.is_empty() => return Err(ParseSizeError::InvalidNumber(value.to_string())),
+        _ => return Err(ParseSizeError::UnknownSuffix(suffix.to_string())),
+    };
+    // Only digits remain, so parsing can only fail if empty or too large
+    let number: u64 = match number {
+        "" => return Err(ParseSizeError::InvalidNumber(value.to_string())),
+        _ => number.parse().map_err(|_| ParseSizeError::Overflow(value.to_string()))?,
+    };
+    number.checked_mul(unit).ok_or_else(|| ParseSizeError::Overflow(value.to_string()))
+}
+
+/// Amount of bytes shown on each line of a hex dump.
+pub const HEXDUMP_WIDTH: usize = 16;
+
+/// Iterate over the lines of a classic offset/hex/ascii dump of the given data.
+///
+/// The `offset` is the address of the first byte, used only for display. Non-printable bytes are
+/// shown as `.` in the ascii column.
+pub fn hexdump_lines(data: &[u8], offset: usize) -> impl Iterator<Item = String> + '_ {
+    data.chunks(HEXDUMP_WIDTH).enumerate().map(move |(idx, chunk)| {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+        format!(
+            "{:08x}  {:<width$}  |{}|",
+            offset + (idx * HEXDUMP_WIDTH),
+            hex.join(" "),
+            ascii,
+            width = (HEXDUMP_WIDTH * 3) - 1
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_size_human() {
+        assert_eq!(FileSize::human(512 * KIBIBYTE), "512 KiB");
+        assert_eq!(FileSize::human(4 * MEBIBYTE), "4 MiB");
+        assert_eq!(FileSize::human(3 * GIBIBYTE / 2), "1.5 GiB");
+        assert_eq!(FileSize::human(100), "100 B");
+        assert_eq!(FileSize::from(MEBIBYTE + 0x1000, MEBIBYTE).to_string(), "1.0");
+    }
+
+    #[test]
+    fn parse_size_suffixes() {
+        assert_eq!(parse_size("4M"), Ok(4_000_000));
+        assert_eq!(parse_size("4Mi"), Ok(4 * MEBIBYTE));
+        assert_eq!(parse_size("512Ki"), Ok(512 * KIBIBYTE));
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("4X"), Err(ParseSizeError::UnknownSuffix(String::from("X"))));
+        assert_eq!(parse_size("Mi"), Err(ParseSizeError::InvalidNumber(String::from("Mi"))));
+        assert_eq!(parse_size(""), Err(ParseSizeError::InvalidNumber(String::new())));
+        assert_eq!(parse_size("17179869184Gi"), Err(ParseSizeError::Overflow(String::from("17179869184Gi"))));
+    }
+
+    #[test]
+    fn hexdump_lines_format() {
+        let data = b"\x80\x37\x12\x40N64ROM TEST\x00\x00\x00\x00\x01";
+        let lines: Vec<String> = hexdump_lines(&data[..], 0x20).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "00000020  80 37 12 40 4e 36 34 52 4f 4d 20 54 45 53 54 00  |.7.@N64ROM TEST.|"
+        );
+        assert_eq!(
+            lines[1],
+            "00000030  00 00 00 01                                      |....|"
+        );
+    }
 }
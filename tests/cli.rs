@@ -0,0 +1,53 @@
+mod common;
+
+use std::fs;
+use std::process::Command;
+
+use serde_json::Value;
+
+use n64rom::rom::{Endianness, Rom};
+
+use common::{fixture, fixture_with_order, temp_file};
+
+/// Run `n64romtool` with the given arguments, returning its standard output.
+fn n64romtool(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_n64romtool")).args(args).output().unwrap();
+    assert!(output.status.success(), "n64romtool {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn show_json() {
+    let path = temp_file("show-json.n64", &fixture_with_order(Endianness::Little));
+    let output = n64romtool(&["show", "--json", path.to_str().unwrap()]);
+    let json: Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(json["name"], "N64ROM FIXTURE");
+    assert_eq!(json["cic"], "unknown");
+    assert_eq!(json["byte_order"], "little");
+    assert_eq!(json["crc1"], 0);
+    assert_eq!(json["crc2"], 0);
+    assert_eq!(json["entry_point"], 0x8000_0400u32);
+    assert_eq!(json["media"], "NFXE");
+    assert_eq!(json["size_bytes"], fixture().len());
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn correct_with_order() {
+    let path = temp_file("correct-order.n64", &fixture_with_order(Endianness::Little));
+    n64romtool(&["correct", "--order", "big", path.to_str().unwrap()]);
+
+    let mut expected = Rom::from_image(fixture_with_order(Endianness::Little)).unwrap();
+    expected.correct_crc();
+    let mut image = Vec::new();
+    expected.write(&mut image, Some(&Endianness::Big)).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), image);
+
+    let rom = Rom::from_path(&path).unwrap();
+    assert_eq!(rom.order(), Endianness::Big);
+    assert!(rom.check_crc().0);
+
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use n64rom::convert;
+use n64rom::rom::{Endianness, HEAD_SIZE};
+
+/// Size of the body of generated fixtures.
+pub const BODY_SIZE: usize = 0x10_0000;
+
+/// Generate a big-endian rom image with a synthetic IPL3 and body.
+///
+/// The header CRC values are left zeroed and are therefore incorrect.
+pub fn fixture() -> Vec<u8> {
+    let mut image = vec![0; HEAD_SIZE + BODY_SIZE];
+    image[0x00..0x04].copy_from_slice(&[0x80, 0x37, 0x12, 0x40]);
+    image[0x04..0x08].copy_from_slice(&15u32.to_be_bytes());
+    image[0x08..0x0C].copy_from_slice(&0x8000_0400u32.to_be_bytes());
+    image[0x20..0x34].copy_from_slice(b"N64ROM FIXTURE      ");
+    image[0x3B..0x3F].copy_from_slice(b"NFXE");
+    // Recognizable but synthetic bootcode pattern
+    for (i, byte) in image[0x40..HEAD_SIZE].iter_mut().enumerate() {
+        *byte = (i as u8).wrapping_mul(3) ^ 0xA5;
+    }
+    for (i, byte) in image[HEAD_SIZE..].iter_mut().enumerate() {
+        *byte = (i * 13 + (i >> 8)) as u8;
+    }
+    image
+}
+
+/// Generate a fixture image in the given byte order.
+pub fn fixture_with_order(order: Endianness) -> Vec<u8> {
+    convert::convert_vec(fixture(), Endianness::Big, order).unwrap()
+}
+
+/// Get a unique path in the temporary directory.
+pub fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("n64rom-test-{}-{}", std::process::id(), name))
+}
+
+/// Write data to a unique temporary file, returning its path.
+pub fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+    let path = temp_path(name);
+    fs::write(&path, data).unwrap();
+    path
+}
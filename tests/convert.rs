@@ -0,0 +1,49 @@
+mod common;
+
+use proptest::prelude::*;
+
+use n64rom::convert;
+use n64rom::ipl3::{Cic, IPL3};
+use n64rom::rom::{Endianness, Rom, HEAD_SIZE};
+
+use common::fixture;
+
+/// Strategy producing synthetic big-endian rom images, with a 4-byte aligned body.
+fn rom_image() -> impl Strategy<Value = Vec<u8>> {
+    (1usize..0x800)
+        .prop_flat_map(|words| prop::collection::vec(any::<u8>(), words * 4))
+        .prop_map(|body| {
+            let mut image = fixture()[..HEAD_SIZE].to_vec();
+            image.extend(body);
+            image
+        })
+}
+
+/// Strategy producing any recognized CIC variant.
+fn cic() -> impl Strategy<Value = Cic> {
+    prop::sample::select(Cic::all())
+}
+
+proptest! {
+    // Each case computes CRC values over the full CRC region, so keep the amount of cases low.
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn convert_preserves_computed_crcs(image in rom_image(), cic in cic()) {
+        let mut rom = Rom::read(&mut &image[..]).unwrap();
+        rom.ipl3 = IPL3::from_cic(cic, *rom.ipl3.get_ipl());
+        let (_, expected) = rom.check_crc();
+
+        // Round trip through little-endian, then normalize back to big-endian.
+        let little = convert::convert_vec(image.clone(), Endianness::Big, Endianness::Little).unwrap();
+        let big = convert::convert_vec(little.clone(), Endianness::Little, Endianness::Big).unwrap();
+        prop_assert_eq!(&big, &image);
+
+        // Reading either image yields the same computed CRC values.
+        for data in &[little, big] {
+            let mut converted = Rom::read(&mut &data[..]).unwrap();
+            converted.ipl3 = IPL3::from_cic(cic, *converted.ipl3.get_ipl());
+            prop_assert_eq!(converted.check_crc().1, expected);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use std::fs;
+
+use n64rom::convert::{self, ConvertStatus};
+use n64rom::ipl3::Cic;
+use n64rom::rom::{Endianness, Rom, HEAD_SIZE};
+
+use common::{fixture, fixture_with_order, temp_file, temp_path};
+
+const ORDERS: [Endianness; 3] = [Endianness::Big, Endianness::Little, Endianness::Mixed];
+
+#[test]
+fn read_fixture_in_each_order() {
+    let expected = fixture();
+    for &order in &ORDERS {
+        let image = fixture_with_order(order);
+        let rom = Rom::read(&mut &image[..]).unwrap();
+        assert_eq!(rom.order(), order);
+        assert_eq!(rom.header.name_str().unwrap().trim(), "N64ROM FIXTURE");
+        assert_eq!(rom.header.media().as_str().unwrap(), "NFXE");
+        assert_eq!(rom.cic(), Cic::Unknown);
+        assert_eq!(rom.ipl3.get_ipl()[..], expected[0x40..HEAD_SIZE]);
+        assert_eq!(rom.data(), &expected[HEAD_SIZE..]);
+    }
+}
+
+#[test]
+fn check_and_correct_crc() {
+    let image = fixture();
+    let mut rom = Rom::read(&mut &image[..]).unwrap();
+    let (result, calc) = rom.check_crc();
+    assert!(!result);
+    assert!(!rom.correct_crc());
+    assert_eq!(rom.check_crc(), (true, calc));
+    assert_eq!(rom.header.crcs(), calc);
+}
+
+#[test]
+fn write_round_trip_in_each_order() {
+    let image = fixture();
+    let rom = Rom::read(&mut &image[..]).unwrap();
+    for &order in &ORDERS {
+        let mut output = Vec::new();
+        let written = rom.write(&mut output, Some(&order)).unwrap();
+        assert_eq!(written, image.len());
+        assert_eq!(output, fixture_with_order(order));
+
+        let reread = Rom::read(&mut &output[..]).unwrap();
+        assert_eq!(reread.order(), order);
+        assert!(reread == rom);
+    }
+}
+
+#[test]
+fn save_and_from_path_round_trip() {
+    let path = temp_path("save.z64");
+    for &order in &ORDERS {
+        let rom = Rom::read(&mut &fixture()[..]).unwrap();
+        assert_eq!(rom.save(&path, Some(order)).unwrap(), fixture().len());
+        assert_eq!(fs::read(&path).unwrap(), fixture_with_order(order));
+
+        let reread = Rom::from_path(&path).unwrap();
+        assert_eq!(reread.order(), order);
+        assert!(reread == rom);
+    }
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn convert_rom_path_between_orders() {
+    for &from in &ORDERS {
+        for &to in &ORDERS {
+            let name = format!("convert-{:?}-{:?}", from, to);
+            let in_path = temp_file(&format!("{}.in", name), &fixture_with_order(from));
+            let out_path = temp_path(&format!("{}.out", name));
+
+            let (result, amount) = convert::convert_rom_path(&in_path, &out_path, to).unwrap();
+            assert_eq!(matches!(result, ConvertStatus::AlreadyConverted(_)), from == to);
+            assert_eq!(result.source(), from);
+            assert_eq!(amount, fixture().len());
+            assert_eq!(fs::read(&out_path).unwrap(), fixture_with_order(to));
+
+            fs::remove_file(in_path).unwrap();
+            fs::remove_file(out_path).unwrap();
+        }
+    }
+}
+
+#[test]
+fn convert_rom_file_reports_progress() {
+    let in_path = temp_file("progress.in", &fixture_with_order(Endianness::Little));
+    let out_path = temp_path("progress.out");
+
+    let mut calls = Vec::new();
+    let mut in_file = fs::File::open(&in_path).unwrap();
+    let mut out_file = fs::File::create(&out_path).unwrap();
+    let (_, amount) = convert::convert_rom_file_with_progress(&mut in_file, &mut out_file, Endianness::Big, |done, total| {
+        calls.push((done, total));
+    })
+    .unwrap();
+
+    assert!(!calls.is_empty());
+    assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    assert!(calls.iter().all(|&(_, total)| total == fixture().len()));
+    assert_eq!(calls.last().unwrap().0, amount);
+    assert_eq!(fs::read(&out_path).unwrap(), fixture());
+
+    fs::remove_file(in_path).unwrap();
+    fs::remove_file(out_path).unwrap();
+}
+
+#[test]
+fn convert_rom_path_onto_itself() {
+    let path = temp_file("convert-in-place", &fixture_with_order(Endianness::Little));
+
+    let (result, amount) = convert::convert_rom_path(&path, &path, Endianness::Big).unwrap();
+    assert_eq!(result, ConvertStatus::Complete(Endianness::Little));
+    assert_eq!(amount, fixture().len());
+    assert_eq!(fs::read(&path).unwrap(), fixture());
+
+    // Also through a different spelling of the same path
+    let dotted = path.parent().unwrap().join(".").join(path.file_name().unwrap());
+    convert::convert_rom_path(&path, &dotted, Endianness::Mixed).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), fixture_with_order(Endianness::Mixed));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn convert_rom_path_onto_itself_with_leftover_temp_file() {
+    let path = temp_file("convert-in-place-leftover", &fixture_with_order(Endianness::Little));
+    let name = path.file_name().unwrap().to_str().unwrap();
+    let leftover = path.with_file_name(format!(".{}.{}-0.tmp", name, std::process::id()));
+    fs::write(&leftover, b"leftover").unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+    }
+
+    convert::convert_rom_path(&path, &path, Endianness::Big).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), fixture());
+    assert_eq!(fs::read(&leftover).unwrap(), b"leftover");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+    }
+
+    fs::remove_file(path).unwrap();
+    fs::remove_file(leftover).unwrap();
+}
+
+#[test]
+fn normalize_rom_path_from_little_endian() {
+    let in_path = temp_file("normalize.in", &fixture_with_order(Endianness::Little));
+    let out_path = temp_path("normalize.out");
+
+    let (result, order) = convert::normalize_rom_path(&in_path, &out_path).unwrap();
+    assert_eq!(result, ConvertStatus::Complete(Endianness::Little));
+    assert_eq!(order, Endianness::Little);
+    assert_eq!(fs::read(&out_path).unwrap(), fixture());
+
+    fs::remove_file(in_path).unwrap();
+    fs::remove_file(out_path).unwrap();
+}
+
+#[test]
+fn convert_rom_path_as_with_corrupt_magic() {
+    let mut image = fixture_with_order(Endianness::Little);
+    image[..4].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+    let in_path = temp_file("convert-as.in", &image);
+    let out_path = temp_path("convert-as.out");
+
+    assert!(convert::convert_rom_path(&in_path, &out_path, Endianness::Big).is_err());
+    convert::convert_rom_path_as(&in_path, &out_path, Endianness::Little, Endianness::Big).unwrap();
+    let output = fs::read(&out_path).unwrap();
+    assert_eq!(&output[..4], &[0x78, 0x56, 0x34, 0x12]);
+    assert_eq!(output[4..], fixture()[4..]);
+
+    fs::remove_file(in_path).unwrap();
+    fs::remove_file(out_path).unwrap();
+}